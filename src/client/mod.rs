@@ -0,0 +1,606 @@
+use crate::codec::{CodecError, MsgCodec};
+use crate::framing::Checksum;
+use crate::ids::IdAllocator;
+use crate::retry::RetryPolicy;
+use crate::{Ack, AckError, Msg, RxData};
+
+use core::fmt::Display;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::BytesMut;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_util::codec::{Decoder, Encoder};
+
+// Server side hub client :: owns the hub transport, allocates message ids and
+// correlates requests with the Ack returned by the hub (Ack.rx_id)
+//
+// >>> let mut client = HubClient::new(TcpStream::connect(addr).await?, epoch);
+// >>> let mut rx = client.rx_stream().unwrap();
+// >>> let id = client.next_id();
+// >>> client.call(&Msg::Send(TxData { id, ... })).await?;
+// >>> ...
+// >>> while let Some(rx) = rx.next().await { ... }
+// >>> ...
+// >>> // Reconnect with a new epoch so the hub does not treat new ids as duplicates
+// >>> client = HubClient::new(TcpStream::connect(addr).await?, client.epoch().wrapping_add(1));
+
+/// Unread RxData/Msg buffered by the client (further messages are dropped)
+pub const STREAM_QUEUE_LEN: usize = 64;
+
+#[derive(Debug)]
+pub enum ClientError {
+    Codec(CodecError),
+    /// Hub returned Ack with status false
+    Nack(Option<AckError>),
+    /// No Ack after all retransmissions
+    Timeout,
+    /// Transport closed (or client dropped) before Ack was received
+    Closed,
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ClientError::Codec(e) => write!(f, "{}", e),
+            ClientError::Nack(Some(e)) => write!(f, "Nack: {}", e),
+            ClientError::Nack(None) => write!(f, "Nack"),
+            ClientError::Timeout => write!(f, "Ack timeout"),
+            ClientError::Closed => write!(f, "Connection closed"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<CodecError> for ClientError {
+    fn from(e: CodecError) -> Self {
+        ClientError::Codec(e)
+    }
+}
+
+impl From<std::io::Error> for ClientError {
+    fn from(e: std::io::Error) -> Self {
+        ClientError::Codec(CodecError::Io(e))
+    }
+}
+
+type Pending = Arc<Mutex<HashMap<u32, oneshot::Sender<Ack>>>>;
+type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Ack for a single request (resolves with the Ack whatever its status)
+#[derive(Debug)]
+pub struct Response {
+    id: u32,
+    rx: oneshot::Receiver<Ack>,
+    pending: Pending,
+}
+
+impl Response {
+    /// Request id (matched against Ack.rx_id)
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub async fn timeout(self, duration: Duration) -> Result<Ack, ClientError> {
+        tokio::time::timeout(duration, self)
+            .await
+            .unwrap_or(Err(ClientError::Timeout))
+    }
+}
+
+impl Future for Response {
+    type Output = Result<Ack, ClientError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx)
+            .poll(cx)
+            .map(|r| r.map_err(|_| ClientError::Closed))
+    }
+}
+
+impl Drop for Response {
+    fn drop(&mut self) {
+        self.rx.close();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        // Only remove our own entry (id may have been reused)
+        if pending.get(&self.id).is_some_and(|tx| tx.is_closed()) {
+            pending.remove(&self.id);
+        }
+    }
+}
+
+/// RxData received from the hub
+#[derive(Debug)]
+pub struct RxStream {
+    rx: mpsc::Receiver<RxData>,
+}
+
+impl RxStream {
+    /// Next RxData (None when the transport is closed)
+    pub async fn next(&mut self) -> Option<RxData> {
+        self.rx.recv().await
+    }
+
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<RxData>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// Unsolicited messages other than Ack/RxData (TxStatus, HubStats, ...)
+#[derive(Debug)]
+pub struct MsgStream {
+    rx: mpsc::Receiver<Msg>,
+}
+
+impl MsgStream {
+    /// Next Msg (None when the transport is closed)
+    pub async fn next(&mut self) -> Option<Msg> {
+        self.rx.recv().await
+    }
+
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Msg>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+pub struct HubClient {
+    writer: tokio::sync::Mutex<(Writer, MsgCodec)>,
+    pending: Pending,
+    ids: Mutex<IdAllocator>,
+    retry: RetryPolicy,
+    retransmits: AtomicU32,
+    rx_stream: Option<RxStream>,
+    msg_stream: Option<MsgStream>,
+    dropped: Arc<AtomicU32>,
+    reader: JoinHandle<()>,
+}
+
+impl HubClient {
+    /// Create client for transport (must be called within a tokio runtime)
+    ///
+    /// Ids are allocated from epoch, which must differ from the epoch used
+    /// by the previous connection to the hub - the hub DuplicateWindow and
+    /// AckCache outlive the connection.
+    pub fn new<T>(io: T, epoch: u8) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        Self::with_checksum(io, Checksum::None, epoch)
+    }
+
+    pub fn with_checksum<T>(io: T, checksum: Checksum, epoch: u8) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (r, w) = tokio::io::split(io);
+        let pending = Pending::default();
+        let dropped = Arc::new(AtomicU32::new(0));
+        let (rx_tx, rx_rx) = mpsc::channel(STREAM_QUEUE_LEN);
+        let (msg_tx, msg_rx) = mpsc::channel(STREAM_QUEUE_LEN);
+        let reader = tokio::spawn(read_task(
+            r,
+            MsgCodec::with_checksum(checksum),
+            pending.clone(),
+            rx_tx,
+            msg_tx,
+            dropped.clone(),
+        ));
+        Self {
+            writer: tokio::sync::Mutex::new((Box::new(w), MsgCodec::with_checksum(checksum))),
+            pending,
+            ids: Mutex::new(IdAllocator::new(epoch)),
+            retry: RetryPolicy::default(),
+            retransmits: AtomicU32::new(0),
+            rx_stream: Some(RxStream { rx: rx_rx }),
+            msg_stream: Some(MsgStream { rx: msg_rx }),
+            dropped,
+            reader,
+        }
+    }
+
+    /// Ack timeout/retransmission policy used by call
+    pub fn set_retry(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    /// Id allocator (eg. continue from restored state)
+    pub fn set_ids(&mut self, ids: IdAllocator) {
+        self.ids = Mutex::new(ids);
+    }
+
+    /// Id epoch of this connection
+    pub fn epoch(&self) -> u8 {
+        self.ids.lock().unwrap_or_else(|e| e.into_inner()).epoch()
+    }
+
+    /// Allocate message id
+    pub fn next_id(&self) -> u32 {
+        self.ids.lock().unwrap_or_else(|e| e.into_inner()).next_id()
+    }
+
+    /// Take RxData stream (only available once)
+    pub fn rx_stream(&mut self) -> Option<RxStream> {
+        self.rx_stream.take()
+    }
+
+    /// Take stream of other unsolicited messages (only available once)
+    pub fn msg_stream(&mut self) -> Option<MsgStream> {
+        self.msg_stream.take()
+    }
+
+    /// RxData/Msg dropped because the stream was not read (or not taken)
+    pub fn dropped(&self) -> u32 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Messages retransmitted by call
+    pub fn retransmits(&self) -> u32 {
+        self.retransmits.load(Ordering::Relaxed)
+    }
+
+    /// Requests waiting for Ack
+    pub fn pending(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Send msg without waiting for Ack
+    pub async fn send(&self, msg: &Msg) -> Result<(), ClientError> {
+        let mut buf = BytesMut::new();
+        let mut writer = self.writer.lock().await;
+        let (w, codec) = &mut *writer;
+        codec.encode(msg, &mut buf)?;
+        w.write_all(&buf).await?;
+        w.flush().await?;
+        Ok(())
+    }
+
+    /// Send msg and return future resolving with the matching Ack
+    ///
+    /// Requests are correlated on msg id - allocate ids with next_id.
+    pub async fn request(&self, msg: &Msg) -> Result<Response, ClientError> {
+        let id = msg.get_id();
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.retain(|_, tx| !tx.is_closed());
+            pending.insert(id, tx);
+        }
+        let response = Response {
+            id,
+            rx,
+            pending: self.pending.clone(),
+        };
+        // Response is dropped (and pending entry removed) on error
+        self.send(msg).await?;
+        Ok(response)
+    }
+
+    /// Send msg and wait for Ack (Ack with status false returns Nack)
+    ///
+    /// The msg is retransmitted with the same id if the Ack does not arrive
+    /// in time (see RetryPolicy) - the hub suppresses duplicates.
+    pub async fn call(&self, msg: &Msg) -> Result<Ack, ClientError> {
+        let mut response = self.request(msg).await?;
+        let mut attempt = 0;
+        let ack = loop {
+            match tokio::time::timeout(self.retry.timeout(attempt), &mut response).await {
+                Ok(ack) => break ack?,
+                Err(_) if attempt < self.retry.retries => {
+                    attempt += 1;
+                    self.retransmits.fetch_add(1, Ordering::Relaxed);
+                    self.send(msg).await?;
+                }
+                Err(_) => return Err(ClientError::Timeout),
+            }
+        };
+        if ack.status {
+            Ok(ack)
+        } else {
+            Err(ClientError::Nack(ack.error))
+        }
+    }
+
+    /// Allocate id, build msg and wait for Ack
+    pub async fn call_with(&self, f: impl FnOnce(u32) -> Msg) -> Result<Ack, ClientError> {
+        self.call(&f(self.next_id())).await
+    }
+}
+
+impl Drop for HubClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+async fn read_task<R>(
+    mut r: R,
+    mut codec: MsgCodec,
+    pending: Pending,
+    rx_tx: mpsc::Sender<RxData>,
+    msg_tx: mpsc::Sender<Msg>,
+    dropped: Arc<AtomicU32>,
+) where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = BytesMut::new();
+    loop {
+        let msg = match codec.decode(&mut buf) {
+            Ok(Some(Ok(msg))) => msg,
+            // Skip bad frame
+            Ok(Some(Err(_))) => continue,
+            Err(_) => break,
+            Ok(None) => match r.read_buf(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => continue,
+            },
+        };
+        let sent = match msg {
+            Msg::Ack(ack) => {
+                let tx = pending
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .remove(&ack.rx_id);
+                // Ack for unknown (or timed out) request is ignored
+                if let Some(tx) = tx {
+                    let _ = tx.send(ack);
+                }
+                true
+            }
+            Msg::Recv(rx) => rx_tx.try_send(rx).is_ok(),
+            msg => msg_tx.try_send(msg).is_ok(),
+        };
+        if !sent {
+            dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    // Outstanding requests resolve with Closed
+    pending.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::{make_id, DuplicateWindow};
+    use crate::retry::AckCache;
+    use crate::tests::fixtures::{tx, A};
+    use crate::{PeerAddress, Ping};
+    use tokio::io::DuplexStream;
+
+    /// Minimal hub on the other end of a duplex stream
+    struct Hub {
+        io: DuplexStream,
+        codec: MsgCodec,
+        buf: BytesMut,
+    }
+
+    impl Hub {
+        async fn recv(&mut self) -> Msg {
+            loop {
+                if let Some(msg) = self.codec.decode(&mut self.buf).unwrap() {
+                    return msg.unwrap();
+                }
+                assert!(self.io.read_buf(&mut self.buf).await.unwrap() > 0);
+            }
+        }
+
+        async fn send(&mut self, msg: Msg) {
+            let mut buf = BytesMut::new();
+            self.codec.encode(msg, &mut buf).unwrap();
+            self.io.write_all(&buf).await.unwrap();
+        }
+    }
+
+    fn client(checksum: Checksum) -> (HubClient, Hub) {
+        let (a, b) = tokio::io::duplex(1024);
+        let hub = Hub {
+            io: b,
+            codec: MsgCodec::with_checksum(checksum),
+            buf: BytesMut::new(),
+        };
+        (HubClient::with_checksum(a, checksum, 0), hub)
+    }
+
+    fn rx(id: u32) -> RxData {
+        RxData {
+            id,
+            src_addr: A,
+            dst_addr: [0xff; 6],
+            data: heapless::Vec::from_slice(b"world").unwrap(),
+            rssi: -50,
+            meta: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_response() {
+        let (client, mut hub) = client(Checksum::Crc16);
+        let r1 = client
+            .request(&Msg::Send(tx(client.next_id(), A)))
+            .await
+            .unwrap();
+        let r2 = client
+            .request(&Msg::Send(tx(client.next_id(), A)))
+            .await
+            .unwrap();
+        assert_eq!((r1.id(), r2.id()), (0, 1));
+        assert_eq!(client.pending(), 2);
+        assert_eq!(hub.recv().await.get_id(), 0);
+        assert_eq!(hub.recv().await.get_id(), 1);
+        // Acks out of order
+        hub.send(Msg::Ack(Ack::error(101, 1, AckError::PeerNotFound)))
+            .await;
+        hub.send(Msg::Ack(Ack::ok(100, 0))).await;
+        assert_eq!(r1.await.unwrap(), Ack::ok(100, 0));
+        assert_eq!(
+            r2.await.unwrap(),
+            Ack::error(101, 1, AckError::PeerNotFound)
+        );
+        assert_eq!(client.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_call() {
+        let (client, mut hub) = client(Checksum::None);
+        let server = tokio::spawn(async move {
+            for i in 0..2 {
+                let id = hub.recv().await.get_id();
+                let ack = match i {
+                    0 => Ack::ok(100, id),
+                    _ => Ack::error(101, id, AckError::NoMem),
+                };
+                hub.send(Msg::Ack(ack)).await;
+            }
+            hub
+        });
+        assert_eq!(
+            client.call_with(|id| Msg::Send(tx(id, A))).await.unwrap(),
+            Ack::ok(100, 0)
+        );
+        assert!(matches!(
+            client.call_with(|id| Msg::Send(tx(id, A))).await,
+            Err(ClientError::Nack(Some(AckError::NoMem)))
+        ));
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_close() {
+        let (mut client, mut hub) = client(Checksum::None);
+        client.set_retry(RetryPolicy::none(Duration::from_millis(10)));
+        assert!(matches!(
+            client.call(&Msg::Send(tx(1, A))).await,
+            Err(ClientError::Timeout)
+        ));
+        // Timed out request is removed and late Ack ignored
+        assert_eq!(client.pending(), 0);
+        hub.recv().await;
+        hub.send(Msg::Ack(Ack::ok(100, 1))).await;
+        let r = client.request(&Msg::Send(tx(2, A))).await.unwrap();
+        drop(hub);
+        assert!(matches!(r.await, Err(ClientError::Closed)));
+    }
+
+    #[tokio::test]
+    async fn test_retransmit() {
+        let (mut client, mut hub) = client(Checksum::Crc16);
+        client.set_retry(RetryPolicy {
+            timeout: Duration::from_millis(20),
+            backoff: 2,
+            max_timeout: Duration::from_millis(40),
+            retries: 2,
+        });
+        let remove = |id| Msg::RemovePeer(PeerAddress { id, address: A });
+        let server = tokio::spawn(async move {
+            let mut acks = AckCache::<4>::new();
+            let mut applied = 0;
+            for i in 0..3 {
+                let msg = hub.recv().await;
+                assert_eq!(msg, remove(0));
+                let ack = match acks.check(&msg) {
+                    Some(ack) => ack.clone(),
+                    None => {
+                        applied += 1;
+                        let ack = Ack::ok(100, msg.get_id());
+                        acks.insert(ack.clone());
+                        ack
+                    }
+                };
+                // First transmission is applied but its Ack is lost
+                if i == 2 {
+                    hub.send(Msg::Ack(ack)).await;
+                }
+            }
+            (hub, applied)
+        });
+        assert_eq!(client.call_with(remove).await.unwrap(), Ack::ok(100, 0));
+        assert_eq!(client.retransmits(), 2);
+        let (_hub, applied) = server.await.unwrap();
+        assert_eq!(applied, 1);
+        // Retry budget exhausted
+        assert!(matches!(
+            client.call(&remove(1)).await,
+            Err(ClientError::Timeout)
+        ));
+        assert_eq!(client.retransmits(), 4);
+        assert_eq!(client.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_streams() {
+        let (mut client, mut hub) = client(Checksum::Crc16);
+        let mut rx_stream = client.rx_stream().unwrap();
+        let mut msg_stream = client.msg_stream().unwrap();
+        assert!(client.rx_stream().is_none());
+        let r = client.request(&Msg::Send(tx(7, A))).await.unwrap();
+        hub.recv().await;
+        hub.send(Msg::Recv(rx(1))).await;
+        hub.send(Msg::Ping(Ping {
+            id: 2,
+            timestamp: 1,
+        }))
+        .await;
+        hub.send(Msg::Ack(Ack::ok(3, 7))).await;
+        hub.send(Msg::Recv(rx(4))).await;
+        assert_eq!(r.await.unwrap(), Ack::ok(3, 7));
+        assert_eq!(rx_stream.next().await, Some(rx(1)));
+        assert_eq!(rx_stream.next().await, Some(rx(4)));
+        assert_eq!(
+            msg_stream.next().await,
+            Some(Msg::Ping(Ping {
+                id: 2,
+                timestamp: 1
+            }))
+        );
+        drop(hub);
+        assert_eq!(rx_stream.next().await, None);
+        assert_eq!(client.dropped(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect() {
+        let remove = |id| Msg::RemovePeer(PeerAddress { id, address: A });
+        // Hub state outlives the connection
+        let mut acks = AckCache::<4>::new();
+        let mut window = DuplicateWindow::new();
+        let mut applied = 0;
+        let mut epoch = 0;
+        for _ in 0..2 {
+            let (a, b) = tokio::io::duplex(1024);
+            let mut hub = Hub {
+                io: b,
+                codec: MsgCodec::new(),
+                buf: BytesMut::new(),
+            };
+            let client = HubClient::new(a, epoch);
+            let server = async {
+                let msg = hub.recv().await;
+                let ack = match acks.check(&msg) {
+                    Some(ack) => ack.clone(),
+                    None => {
+                        assert!(window.check(msg.get_id()));
+                        applied += 1;
+                        let ack = Ack::ok(100, msg.get_id());
+                        acks.insert(ack.clone());
+                        ack
+                    }
+                };
+                hub.send(Msg::Ack(ack)).await;
+            };
+            let (ack, ()) = tokio::join!(client.call_with(remove), server);
+            assert_eq!(ack.unwrap().rx_id, make_id(epoch, 0));
+            epoch = client.epoch().wrapping_add(1);
+        }
+        assert_eq!(applied, 2);
+        // Reusing an epoch would be acked from the cache without being applied
+        assert!(acks.check(&remove(make_id(0, 0))).is_some());
+        assert!(window.is_duplicate(make_id(0, 1)));
+    }
+}
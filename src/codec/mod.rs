@@ -0,0 +1,274 @@
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::framing::{
+    decode_frame_with, encode_frame_with, Checksum, FRAME_DELIMITER, MAX_FRAME_LEN,
+};
+use crate::types::msg::MsgError;
+use crate::Msg;
+
+use core::fmt::Display;
+
+// tokio-util codec for Msg streams using the framing module wire format
+//
+// >>> let mut hub = Framed::new(TcpStream::connect(addr).await?, MsgCodec::new());
+// >>> while let Some(frame) = hub.next().await {
+// >>>     let msg = match frame? { Ok(msg) => msg, Err(e) => { warn!("{}", e); continue } };
+// >>>     ...
+// >>> }
+
+#[derive(Debug)]
+pub enum CodecError {
+    Io(std::io::Error),
+    Msg(MsgError),
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CodecError::Io(e) => write!(f, "IO error: {}", e),
+            CodecError::Msg(e) => write!(f, "Msg error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+impl From<std::io::Error> for CodecError {
+    fn from(e: std::io::Error) -> Self {
+        CodecError::Io(e)
+    }
+}
+
+impl From<MsgError> for CodecError {
+    fn from(e: MsgError) -> Self {
+        CodecError::Msg(e)
+    }
+}
+
+/// Msg Encoder/Decoder
+///
+/// Each frame is decoded to a `Result<Msg, MsgError>` item so a corrupt frame
+/// does not end a `Framed` stream (which terminates after the first `Err`).
+/// The stream error is only used for I/O errors. Frames longer than
+/// `max_frame_len` (default `MAX_FRAME_LEN`) are discarded up to the next
+/// delimiter and reported as `MsgError::CapacityError`.
+#[derive(Clone, Debug)]
+pub struct MsgCodec {
+    checksum: Checksum,
+    max_frame_len: usize,
+    // Scan offset into the current (incomplete) frame
+    next_index: usize,
+    discarding: bool,
+}
+
+impl MsgCodec {
+    pub fn new() -> Self {
+        Self::with_checksum(Checksum::None)
+    }
+
+    pub fn with_checksum(checksum: Checksum) -> Self {
+        Self {
+            checksum,
+            max_frame_len: MAX_FRAME_LEN,
+            next_index: 0,
+            discarding: false,
+        }
+    }
+
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Lower the frame length limit (eg. for a link with a smaller MTU) -
+    /// clamped to 1..=MAX_FRAME_LEN
+    pub fn set_max_frame_len(&mut self, max_frame_len: usize) {
+        self.max_frame_len = max_frame_len.clamp(1, MAX_FRAME_LEN);
+    }
+}
+
+impl Default for MsgCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for MsgCodec {
+    type Item = Result<Msg, MsgError>;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, CodecError> {
+        loop {
+            if self.discarding {
+                // Drop the oversize frame up to and including the next delimiter
+                return Ok(match src.iter().position(|&b| b == FRAME_DELIMITER) {
+                    Some(i) => {
+                        src.advance(i + 1);
+                        self.discarding = false;
+                        Some(Err(MsgError::CapacityError))
+                    }
+                    None => {
+                        src.advance(src.len());
+                        None
+                    }
+                });
+            }
+            let end = src.len().min(self.max_frame_len);
+            match src[self.next_index..end]
+                .iter()
+                .position(|&b| b == FRAME_DELIMITER)
+            {
+                Some(i) => {
+                    let mut frame = src.split_to(self.next_index + i + 1);
+                    self.next_index = 0;
+                    let len = frame.len() - 1;
+                    if len == 0 {
+                        // Skip empty frames
+                        continue;
+                    }
+                    return Ok(Some(decode_frame_with(&mut frame[..len], self.checksum)));
+                }
+                None if src.len() >= self.max_frame_len => {
+                    // Oversize frame - data following it may already be buffered
+                    src.advance(end);
+                    self.next_index = 0;
+                    self.discarding = true;
+                }
+                None => {
+                    self.next_index = end;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+impl Encoder<&Msg> for MsgCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, msg: &Msg, dst: &mut BytesMut) -> Result<(), CodecError> {
+        let mut buf = [0_u8; MAX_FRAME_LEN];
+        let frame = encode_frame_with(msg, self.checksum, &mut buf)?;
+        dst.extend_from_slice(frame);
+        Ok(())
+    }
+}
+
+impl Encoder<Msg> for MsgCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, msg: Msg, dst: &mut BytesMut) -> Result<(), CodecError> {
+        self.encode(&msg, dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{tx, A};
+    use crate::*;
+
+    fn decode_all(codec: &mut MsgCodec, src: &mut BytesMut) -> Vec<Result<Msg, MsgError>> {
+        let mut out = Vec::new();
+        loop {
+            match codec.decode(src) {
+                Ok(Some(m)) => out.push(m),
+                Ok(None) => break,
+                Err(e) => panic!("{}", e),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_codec_roundtrip() {
+        let mut codec = MsgCodec::with_checksum(Checksum::Crc16);
+        let long = TxData {
+            data: heapless::Vec::from_slice(&[0; 100]).unwrap(),
+            ..tx(2, A)
+        };
+        let msgs = [Msg::Send(tx(1, A)), Msg::Send(long), Msg::Send(tx(3, A))];
+        let mut buf = BytesMut::new();
+        for m in &msgs {
+            codec.encode(m, &mut buf).unwrap();
+        }
+        assert_eq!(decode_all(&mut codec, &mut buf), msgs.map(Ok));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_codec_partial() {
+        let mut codec = MsgCodec::new();
+        let mut encoded = BytesMut::new();
+        codec.encode(Msg::Send(tx(1, A)), &mut encoded).unwrap();
+        let mut buf = BytesMut::new();
+        let mut out = Vec::new();
+        for b in encoded.iter() {
+            buf.extend_from_slice(&[*b]);
+            out.extend(decode_all(&mut codec, &mut buf));
+        }
+        assert_eq!(out, [Ok(Msg::Send(tx(1, A)))]);
+    }
+
+    #[test]
+    fn test_codec_oversize() {
+        let mut codec = MsgCodec::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&[0xaa; MAX_FRAME_LEN + 10]);
+        assert!(decode_all(&mut codec, &mut buf).is_empty());
+        buf.extend_from_slice(&[0xaa, 0xaa, 0x00]);
+        codec.encode(Msg::Send(tx(2, A)), &mut buf).unwrap();
+        assert_eq!(
+            decode_all(&mut codec, &mut buf),
+            [Err(MsgError::CapacityError), Ok(Msg::Send(tx(2, A)))]
+        );
+    }
+
+    #[test]
+    fn test_codec_max_frame_len() {
+        let mut codec = MsgCodec::new();
+        codec.set_max_frame_len(16);
+        assert_eq!(codec.max_frame_len(), 16);
+        let mut buf = BytesMut::new();
+        codec.encode(Msg::Send(tx(1, A)), &mut buf).unwrap();
+        codec.encode(Msg::Ack(Ack::ok(2, 1)), &mut buf).unwrap();
+        assert_eq!(
+            decode_all(&mut codec, &mut buf),
+            [Err(MsgError::CapacityError), Ok(Msg::Ack(Ack::ok(2, 1)))]
+        );
+        codec.set_max_frame_len(usize::MAX);
+        assert_eq!(codec.max_frame_len(), MAX_FRAME_LEN);
+    }
+
+    #[tokio::test]
+    async fn test_codec_framed() {
+        use futures::StreamExt;
+        use tokio::io::AsyncWriteExt;
+        use tokio_util::codec::FramedRead;
+
+        let mut codec = MsgCodec::with_checksum(Checksum::Crc16);
+        let mut buf = BytesMut::new();
+        codec.encode(Msg::Send(tx(1, A)), &mut buf).unwrap();
+        // Corrupt data (checksum mismatch)
+        let i = buf.iter().position(|&b| b == b'h').unwrap();
+        buf[i] = b'H';
+        codec.encode(Msg::Send(tx(2, A)), &mut buf).unwrap();
+        buf.extend_from_slice(&[0xaa; MAX_FRAME_LEN + 10]);
+        buf.extend_from_slice(&[0x00]);
+        codec.encode(Msg::Send(tx(3, A)), &mut buf).unwrap();
+
+        let (mut w, r) = tokio::io::duplex(64);
+        let writer = tokio::spawn(async move { w.write_all(&buf).await });
+        let items = FramedRead::new(r, codec)
+            .map(|item| item.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        writer.await.unwrap().unwrap();
+        // Bad frames are reported and decoding continues to the end of stream
+        assert_eq!(items.len(), 4);
+        assert!(items[0].is_err());
+        assert_eq!(items[1], Ok(Msg::Send(tx(2, A))));
+        assert_eq!(items[2], Err(MsgError::CapacityError));
+        assert_eq!(items[3], Ok(Msg::Send(tx(3, A))));
+    }
+}
@@ -0,0 +1,220 @@
+use crate::types::{DeferredDepth, DeferredStatus, MAX_DEFERRED_PEERS};
+use crate::{AckError, TxData};
+
+// Hub side deferred send queue
+//
+// Reference implementation of the deferred send semantics (see
+// types::deferred). Timestamps (ms) are supplied by the caller:
+//
+// >>> Msg::Send(tx) if tx.defer => match queue.push(tx, now) { ... }  // Ack
+// >>> Msg::FlushDeferred(f) => queue.flush(f.dst_addr.as_ref()),
+// >>> Msg::DiscardDeferred(d) => queue.discard(&d.dst_addr),
+// >>> Msg::GetDeferred(g) => Msg::DeferredStatus(queue.status(next_id(), g.id, now)),
+// >>> ...
+// >>> // ESP-NOW RX callback
+// >>> queue.wake(&src_addr, now);
+// >>> ...
+// >>> while let Some(tx) = queue.poll(now) { send(tx) }  // TxStatus
+
+#[derive(Clone, Debug)]
+struct Entry {
+    tx: TxData,
+    queued: u64,
+    flush: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct DeferredQueue<const N: usize = 16> {
+    entries: heapless::Vec<Entry, N>,
+    /// Peers awake until (ms)
+    awake: heapless::Vec<([u8; 6], u64), N>,
+    wake_window: u16,
+}
+
+impl<const N: usize> DeferredQueue<N> {
+    pub const fn new(wake_window: u16) -> Self {
+        Self {
+            entries: heapless::Vec::new(),
+            awake: heapless::Vec::new(),
+            wake_window,
+        }
+    }
+
+    /// Apply HubConfig.wake_window (ms)
+    pub fn set_wake_window(&mut self, wake_window: u16) {
+        self.wake_window = wake_window;
+    }
+
+    pub fn wake_window(&self) -> u16 {
+        self.wake_window
+    }
+
+    /// Queue deferred TxData
+    pub fn push(&mut self, tx: TxData, now: u64) -> Result<(), AckError> {
+        self.entries
+            .push(Entry {
+                tx,
+                queued: now,
+                flush: false,
+            })
+            .map_err(|_| AckError::DeferQueueFull)
+    }
+
+    /// Frame received from peer - send queued messages for wake_window ms
+    pub fn wake(&mut self, addr: &[u8; 6], now: u64) {
+        let until = now + self.wake_window as u64;
+        self.awake.retain(|(_, t)| *t >= now);
+        if let Some(w) = self.awake.iter_mut().find(|(a, _)| a == addr) {
+            w.1 = until;
+        } else if self.awake.push((*addr, until)).is_err() {
+            // Replace the peer that goes to sleep first
+            if let Some(w) = self.awake.iter_mut().min_by_key(|(_, t)| *t) {
+                *w = (*addr, until);
+            }
+        }
+    }
+
+    pub fn is_awake(&self, addr: &[u8; 6], now: u64) -> bool {
+        self.awake.iter().any(|(a, t)| a == addr && *t >= now)
+    }
+
+    /// Send queued messages on next poll (all peers if addr is None)
+    pub fn flush(&mut self, addr: Option<&[u8; 6]>) {
+        self.entries
+            .iter_mut()
+            .filter(|e| addr.is_none_or(|a| &e.tx.dst_addr == a))
+            .for_each(|e| e.flush = true);
+    }
+
+    /// Drop queued messages for addr - returns number discarded
+    pub fn discard(&mut self, addr: &[u8; 6]) -> usize {
+        let len = self.entries.len();
+        self.entries.retain(|e| &e.tx.dst_addr != addr);
+        len - self.entries.len()
+    }
+
+    /// Next message to send at now (in queue order)
+    pub fn poll(&mut self, now: u64) -> Option<TxData> {
+        let i = self
+            .entries
+            .iter()
+            .position(|e| e.flush || self.is_awake(&e.tx.dst_addr, now))?;
+        Some(self.entries.remove(i).tx)
+    }
+
+    /// Queued messages for addr
+    pub fn depth(&self, addr: &[u8; 6]) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| &e.tx.dst_addr == addr)
+            .count()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Reply to GetDeferred
+    pub fn status(&self, id: u32, rx_id: u32, now: u64) -> DeferredStatus {
+        let mut queues = heapless::Vec::<DeferredDepth, MAX_DEFERRED_PEERS>::new();
+        for e in &self.entries {
+            let age = now.saturating_sub(e.queued).min(u32::MAX as u64) as u32;
+            match queues.iter_mut().find(|q| q.dst_addr == e.tx.dst_addr) {
+                Some(q) => q.depth = q.depth.saturating_add(1),
+                None => {
+                    // Entries are in queue order so the first is the oldest
+                    let _ = queues.push(DeferredDepth {
+                        dst_addr: e.tx.dst_addr,
+                        depth: 1,
+                        age,
+                    });
+                }
+            }
+        }
+        DeferredStatus { id, rx_id, queues }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{drain, tx, A, B};
+
+    #[test]
+    fn test_wake() {
+        let mut q = DeferredQueue::<8>::new(50);
+        q.push(tx(1, A), 0).unwrap();
+        q.push(tx(2, B), 0).unwrap();
+        q.push(tx(3, A), 0).unwrap();
+        assert!(q.poll(10).is_none());
+        q.wake(&A, 100);
+        assert_eq!(drain(|| q.poll(100)), vec![1, 3]);
+        // Queued while awake
+        q.push(tx(4, A), 120).unwrap();
+        assert_eq!(drain(|| q.poll(150)), vec![4]);
+        // Window expired
+        q.push(tx(5, A), 151).unwrap();
+        assert!(q.poll(151).is_none());
+        assert_eq!(q.depth(&A), 1);
+        assert_eq!(q.depth(&B), 1);
+    }
+
+    #[test]
+    fn test_flush_discard() {
+        let mut q = DeferredQueue::<8>::new(0);
+        q.push(tx(1, A), 0).unwrap();
+        q.push(tx(2, B), 0).unwrap();
+        q.push(tx(3, B), 0).unwrap();
+        q.flush(Some(&A));
+        assert_eq!(drain(|| q.poll(0)), vec![1]);
+        q.push(tx(4, A), 0).unwrap();
+        assert_eq!(q.discard(&B), 2);
+        assert_eq!(q.discard(&B), 0);
+        q.flush(None);
+        assert_eq!(drain(|| q.poll(0)), vec![4]);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn test_full() {
+        let mut q = DeferredQueue::<2>::new(0);
+        q.push(tx(1, A), 0).unwrap();
+        q.push(tx(2, A), 0).unwrap();
+        assert_eq!(q.push(tx(3, A), 0), Err(AckError::DeferQueueFull));
+        // Awake table is bounded - latest wake wins
+        q.wake(&B, 0);
+        q.wake(&[0; 6], 10);
+        q.wake(&A, 20);
+        assert!(q.is_awake(&A, 20));
+        assert_eq!(drain(|| q.poll(20)), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_status() {
+        let mut q = DeferredQueue::<8>::new(0);
+        q.push(tx(1, A), 100).unwrap();
+        q.push(tx(2, B), 200).unwrap();
+        q.push(tx(3, A), 300).unwrap();
+        let s = q.status(1, 2, 1000);
+        assert_eq!(
+            s.queues.as_slice(),
+            &[
+                DeferredDepth {
+                    dst_addr: A,
+                    depth: 2,
+                    age: 900
+                },
+                DeferredDepth {
+                    dst_addr: B,
+                    depth: 1,
+                    age: 800
+                }
+            ]
+        );
+        assert_eq!(s.total(), 3);
+    }
+}
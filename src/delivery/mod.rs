@@ -0,0 +1,180 @@
+use crate::{Msg, TxStatus};
+
+use core::fmt::Display;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+// Server side delivery tracking :: resolve a future per TxData with the
+// TxStatus reported by the hub
+//
+// >>> let delivery = tracker.track(tx.id);
+// >>> hub.send(Msg::Send(tx)).await?;
+// >>> ...
+// >>> // RX task
+// >>> tracker.handle(&msg);
+// >>> ...
+// >>> match delivery.timeout(Duration::from_secs(1)).await { ... }
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeliveryError {
+    /// Tracking cancelled (or replaced by a send with the same id)
+    Cancelled,
+    Timeout,
+}
+
+impl Display for DeliveryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DeliveryError::Cancelled => write!(f, "delivery cancelled"),
+            DeliveryError::Timeout => write!(f, "delivery timeout"),
+        }
+    }
+}
+
+impl std::error::Error for DeliveryError {}
+
+/// Final delivery outcome for a single TxData
+#[derive(Debug)]
+pub struct Delivery {
+    tx_id: u32,
+    rx: oneshot::Receiver<TxStatus>,
+}
+
+impl Delivery {
+    pub fn tx_id(&self) -> u32 {
+        self.tx_id
+    }
+
+    pub async fn timeout(self, duration: Duration) -> Result<TxStatus, DeliveryError> {
+        tokio::time::timeout(duration, self)
+            .await
+            .unwrap_or(Err(DeliveryError::Timeout))
+    }
+}
+
+impl Future for Delivery {
+    type Output = Result<TxStatus, DeliveryError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx)
+            .poll(cx)
+            .map(|r| r.map_err(|_| DeliveryError::Cancelled))
+    }
+}
+
+/// Pending deliveries keyed by TxData.id (cheap to clone - shared state)
+#[derive(Clone, Debug, Default)]
+pub struct DeliveryTracker {
+    pending: Arc<Mutex<HashMap<u32, oneshot::Sender<TxStatus>>>>,
+}
+
+impl DeliveryTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track delivery of TxData with tx_id (call before sending)
+    pub fn track(&self, tx_id: u32) -> Delivery {
+        let (tx, rx) = oneshot::channel();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        // Drop entries whose Delivery has gone away
+        pending.retain(|_, tx| !tx.is_closed());
+        pending.insert(tx_id, tx);
+        Delivery { tx_id, rx }
+    }
+
+    /// Resolve delivery for status - returns false if tx_id is not tracked
+    pub fn resolve(&self, status: &TxStatus) -> bool {
+        let tx = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&status.tx_id);
+        match tx {
+            Some(tx) => tx.send(status.clone()).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Resolve delivery if msg is TxStatus
+    pub fn handle(&self, msg: &Msg) -> bool {
+        match msg {
+            Msg::TxStatus(status) => self.resolve(status),
+            _ => false,
+        }
+    }
+
+    pub fn cancel(&self, tx_id: u32) -> bool {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&tx_id)
+            .is_some()
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(tx_id: u32, success: bool) -> TxStatus {
+        TxStatus {
+            id: 100 + tx_id,
+            tx_id,
+            dst_addr: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+            success,
+            retries: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let tracker = DeliveryTracker::new();
+        let d1 = tracker.track(1);
+        let d2 = tracker.track(2);
+        assert_eq!(tracker.pending(), 2);
+        assert!(tracker.handle(&Msg::TxStatus(status(2, false))));
+        assert!(tracker.resolve(&status(1, true)));
+        assert!(!tracker.resolve(&status(3, true)));
+        assert_eq!(d1.await, Ok(status(1, true)));
+        assert_eq!(d2.await, Ok(status(2, false)));
+        assert_eq!(tracker.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let tracker = DeliveryTracker::new();
+        let d = tracker.track(1);
+        assert!(tracker.cancel(1));
+        assert_eq!(d.await, Err(DeliveryError::Cancelled));
+        // Replaced by send with same id
+        let d = tracker.track(2);
+        let d2 = tracker.track(2);
+        assert_eq!(d.await, Err(DeliveryError::Cancelled));
+        tracker.resolve(&status(2, true));
+        assert_eq!(d2.await, Ok(status(2, true)));
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let tracker = DeliveryTracker::new();
+        let d = tracker.track(1);
+        assert_eq!(
+            d.timeout(Duration::from_millis(10)).await,
+            Err(DeliveryError::Timeout)
+        );
+        // Dropped delivery is cleaned up on next track
+        let _d = tracker.track(2);
+        assert_eq!(tracker.pending(), 1);
+    }
+}
@@ -0,0 +1,474 @@
+use crate::{RxData, TxData, MAX_DATA_LEN, MAX_DATA_LEN_V1};
+
+use core::fmt::Display;
+
+// Fragmentation :: split payloads larger than MAX_DATA_LEN across multiple
+// TxData frames and reassemble RxData fragments at the receiver.
+//
+// Each fragment carries a small header in front of the payload chunk:
+//
+//   [FRAGMENT_TAG][msg_id: u16][index: u8][count: u8][chunk: u16][data...]
+//
+// `chunk` is the payload length of every fragment except the last, so the
+// receiver can place fragments arriving in any order.
+//
+// Fragments are recognised by the leading FRAGMENT_TAG and ordinary payloads
+// are not escaped, so peers using this layer must send every payload through
+// fragment() - a payload that fits in one frame is sent as a single fragment.
+// A raw payload starting with FRAGMENT_TAG would otherwise be misread.
+
+pub const FRAGMENT_TAG: u8 = 0xf7;
+pub const FRAGMENT_HEADER_LEN: usize = 7;
+/// Default payload carried by a single fragment (fits ESP-NOW v1 frames)
+pub const FRAGMENT_CHUNK: usize = MAX_DATA_LEN_V1 - FRAGMENT_HEADER_LEN;
+/// Maximum number of fragments per message
+pub const MAX_FRAGMENTS: usize = u8::MAX as usize;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum FragmentError {
+    NotFragment,
+    InvalidHeader,
+    TooLarge,
+    Inconsistent,
+}
+
+impl Display for FragmentError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s = match self {
+            FragmentError::NotFragment => "not a fragment",
+            FragmentError::InvalidHeader => "invalid fragment header",
+            FragmentError::TooLarge => "payload too large",
+            FragmentError::Inconsistent => "inconsistent fragment",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FragmentHeader {
+    pub msg_id: u16,
+    pub index: u8,
+    pub count: u8,
+    pub chunk: u16,
+}
+
+impl FragmentHeader {
+    pub fn parse(data: &[u8]) -> Result<(Self, &[u8]), FragmentError> {
+        if data.first() != Some(&FRAGMENT_TAG) {
+            return Err(FragmentError::NotFragment);
+        }
+        if data.len() < FRAGMENT_HEADER_LEN {
+            return Err(FragmentError::InvalidHeader);
+        }
+        let header = Self {
+            msg_id: u16::from_le_bytes([data[1], data[2]]),
+            index: data[3],
+            count: data[4],
+            chunk: u16::from_le_bytes([data[5], data[6]]),
+        };
+        let body = &data[FRAGMENT_HEADER_LEN..];
+        let last = header.index.checked_add(1) == Some(header.count);
+        if header.count == 0
+            || header.index >= header.count
+            || header.chunk == 0
+            || body.len() > header.chunk as usize
+            || (!last && body.len() != header.chunk as usize)
+        {
+            return Err(FragmentError::InvalidHeader);
+        }
+        Ok((header, body))
+    }
+
+    fn write(&self, buf: &mut heapless::Vec<u8, MAX_DATA_LEN>) {
+        let [id_lo, id_hi] = self.msg_id.to_le_bytes();
+        let [chunk_lo, chunk_hi] = self.chunk.to_le_bytes();
+        buf.extend_from_slice(&[
+            FRAGMENT_TAG,
+            id_lo,
+            id_hi,
+            self.index,
+            self.count,
+            chunk_lo,
+            chunk_hi,
+        ])
+        .unwrap_or(()); // SAFE
+    }
+}
+
+/// True if data parses as a fragment (see module comment - payloads are not
+/// escaped so this only distinguishes fragments from payloads sent by peers
+/// that do not use this layer)
+pub fn is_fragment(data: &[u8]) -> bool {
+    FragmentHeader::parse(data).is_ok()
+}
+
+/// Iterator over fragment payloads (header + chunk) for a message
+pub struct Fragments<'a> {
+    payload: &'a [u8],
+    msg_id: u16,
+    chunk: usize,
+    index: u8,
+    count: u8,
+}
+
+/// Split payload into fragments of at most MAX_DATA_LEN_V1 bytes
+pub fn fragment(msg_id: u16, payload: &[u8]) -> Result<Fragments<'_>, FragmentError> {
+    fragment_with_len(msg_id, payload, MAX_DATA_LEN_V1)
+}
+
+/// Split payload into fragments of at most max_data_len bytes
+///
+/// Use `handshake::max_data_len` to size fragments for the hub's ESP-NOW version
+pub fn fragment_with_len(
+    msg_id: u16,
+    payload: &[u8],
+    max_data_len: usize,
+) -> Result<Fragments<'_>, FragmentError> {
+    let chunk = max_data_len
+        .min(MAX_DATA_LEN)
+        .checked_sub(FRAGMENT_HEADER_LEN)
+        .filter(|&c| c > 0)
+        .ok_or(FragmentError::InvalidHeader)?;
+    let count = payload.len().div_ceil(chunk).max(1);
+    if count > MAX_FRAGMENTS {
+        return Err(FragmentError::TooLarge);
+    }
+    Ok(Fragments {
+        payload,
+        msg_id,
+        chunk,
+        index: 0,
+        count: count as u8,
+    })
+}
+
+/// Split payload into TxData frames - next_id allocates the Msg id for each frame
+pub fn fragment_tx<'a>(
+    dst_addr: [u8; 6],
+    msg_id: u16,
+    payload: &'a [u8],
+    defer: bool,
+    mut next_id: impl FnMut() -> u32 + 'a,
+) -> Result<impl Iterator<Item = TxData> + 'a, FragmentError> {
+    Ok(fragment(msg_id, payload)?.map(move |data| TxData {
+        id: next_id(),
+        dst_addr,
+        data,
+        defer,
+        schedule: None,
+    }))
+}
+
+impl Fragments<'_> {
+    pub fn count(&self) -> usize {
+        self.count as usize
+    }
+}
+
+impl Iterator for Fragments<'_> {
+    type Item = heapless::Vec<u8, MAX_DATA_LEN>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        let start = self.index as usize * self.chunk;
+        let end = (start + self.chunk).min(self.payload.len());
+        let mut data = heapless::Vec::new();
+        FragmentHeader {
+            msg_id: self.msg_id,
+            index: self.index,
+            count: self.count,
+            chunk: self.chunk as u16,
+        }
+        .write(&mut data);
+        data.extend_from_slice(&self.payload[start..end])
+            .unwrap_or(()); // SAFE
+        self.index += 1;
+        Some(data)
+    }
+}
+
+// Partially received message
+struct Partial<const MAX_LEN: usize> {
+    src_addr: [u8; 6],
+    msg_id: u16,
+    count: u8,
+    chunk: u16,
+    received: [u32; 8],
+    len: Option<usize>,
+    started: u64,
+    complete: bool,
+    data: [u8; MAX_LEN],
+}
+
+impl<const MAX_LEN: usize> Partial<MAX_LEN> {
+    fn is_complete(&self) -> bool {
+        (0..self.count as usize).all(|i| self.received[i / 32] & (1 << (i % 32)) != 0)
+    }
+}
+
+/// Fragment reassembler
+///
+/// Tracks up to SLOTS concurrent messages (keyed by src_addr and msg_id) of up
+/// to MAX_LEN bytes each. Incomplete messages are discarded after timeout_ms or
+/// when a new message needs the slot (oldest first). Timestamps are supplied
+/// by the caller so this works without a system clock.
+pub struct Reassembler<const SLOTS: usize, const MAX_LEN: usize> {
+    slots: heapless::Vec<Partial<MAX_LEN>, SLOTS>,
+    timeout_ms: u64,
+}
+
+impl<const SLOTS: usize, const MAX_LEN: usize> Reassembler<SLOTS, MAX_LEN> {
+    pub const fn new(timeout_ms: u64) -> Self {
+        Self {
+            slots: heapless::Vec::new(),
+            timeout_ms,
+        }
+    }
+
+    /// Number of messages currently being reassembled
+    pub fn pending(&self) -> usize {
+        self.slots.iter().filter(|s| !s.complete).count()
+    }
+
+    /// Discard incomplete messages older than timeout_ms
+    pub fn expire(&mut self, now_ms: u64) {
+        let timeout = self.timeout_ms;
+        self.slots
+            .retain(|s| !s.complete && now_ms.saturating_sub(s.started) < timeout);
+    }
+
+    /// Add RxData fragment - returns completed payload
+    pub fn push_rx(&mut self, now_ms: u64, rx: &RxData) -> Result<Option<&[u8]>, FragmentError> {
+        self.push(now_ms, rx.src_addr, &rx.data)
+    }
+
+    /// Add fragment from src_addr - returns completed payload
+    ///
+    /// The returned payload remains valid until the next call.
+    pub fn push(
+        &mut self,
+        now_ms: u64,
+        src_addr: [u8; 6],
+        data: &[u8],
+    ) -> Result<Option<&[u8]>, FragmentError> {
+        self.expire(now_ms);
+        let (header, body) = FragmentHeader::parse(data)?;
+        // All but the last fragment are full chunks
+        if (header.count as usize - 1) * header.chunk as usize >= MAX_LEN {
+            return Err(FragmentError::TooLarge);
+        }
+
+        let i = match self
+            .slots
+            .iter()
+            .position(|s| s.src_addr == src_addr && s.msg_id == header.msg_id)
+        {
+            Some(i) => i,
+            None => {
+                if self.slots.is_full() {
+                    // Evict oldest
+                    let (oldest, _) = self
+                        .slots
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, s)| s.started)
+                        .ok_or(FragmentError::TooLarge)?;
+                    self.slots.swap_remove(oldest);
+                }
+                self.slots
+                    .push(Partial {
+                        src_addr,
+                        msg_id: header.msg_id,
+                        count: header.count,
+                        chunk: header.chunk,
+                        received: [0; 8],
+                        len: None,
+                        started: now_ms,
+                        complete: false,
+                        data: [0; MAX_LEN],
+                    })
+                    .map_err(|_| FragmentError::TooLarge)?;
+                self.slots.len() - 1
+            }
+        };
+
+        let slot = &mut self.slots[i];
+        if slot.count != header.count || slot.chunk != header.chunk {
+            self.slots.swap_remove(i);
+            return Err(FragmentError::Inconsistent);
+        }
+        let offset = header.index as usize * header.chunk as usize;
+        let end = offset + body.len();
+        if end > MAX_LEN {
+            self.slots.swap_remove(i);
+            return Err(FragmentError::TooLarge);
+        }
+        slot.data[offset..end].copy_from_slice(body);
+        slot.received[header.index as usize / 32] |= 1 << (header.index as usize % 32);
+        if header.index + 1 == header.count {
+            slot.len = Some(end);
+        }
+        if slot.is_complete() {
+            slot.complete = true;
+            let len = slot.len.unwrap_or(0);
+            return Ok(Some(&self.slots[i].data[..len]));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: [u8; 6] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_fragment_sizes() {
+        let p = payload(4000);
+        let frags: Vec<_> = fragment(1, &p).unwrap().collect();
+        assert_eq!(frags.len(), 4000_usize.div_ceil(FRAGMENT_CHUNK));
+        assert!(frags.iter().all(|f| f.len() <= MAX_DATA_LEN_V1));
+        assert!(frags.iter().all(|f| is_fragment(f)));
+        assert_eq!(fragment(1, &[]).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_fragment_with_len() {
+        let p = payload(1000);
+        let frags: Vec<_> = fragment_with_len(1, &p, 107).unwrap().collect();
+        assert_eq!(frags.len(), 10);
+        assert!(frags.iter().all(|f| f.len() <= 107));
+        assert!(fragment_with_len(1, &p, FRAGMENT_HEADER_LEN).is_err());
+        let mut r = Reassembler::<1, 1024>::new(1000);
+        let mut out = None;
+        for f in &frags {
+            out = r.push(0, SRC, f).unwrap().map(|d| d.to_vec());
+        }
+        assert_eq!(out, Some(p));
+    }
+
+    #[test]
+    fn test_fragment_too_large() {
+        let p = payload(FRAGMENT_CHUNK * MAX_FRAGMENTS + 1);
+        assert!(matches!(fragment(1, &p), Err(FragmentError::TooLarge)));
+    }
+
+    #[test]
+    fn test_fragment_tx() {
+        let p = payload(600);
+        let mut id = 100;
+        let tx: Vec<_> = fragment_tx(SRC, 7, &p, false, || {
+            id += 1;
+            id
+        })
+        .unwrap()
+        .collect();
+        assert_eq!(tx.len(), 3);
+        assert_eq!(tx.iter().map(|t| t.id).collect::<Vec<_>>(), [101, 102, 103]);
+    }
+
+    #[test]
+    fn test_reassemble_in_order() {
+        let p = payload(1000);
+        let mut r = Reassembler::<2, 4096>::new(1000);
+        let frags: Vec<_> = fragment(1, &p).unwrap().collect();
+        let (last, rest) = frags.split_last().unwrap();
+        for f in rest {
+            assert_eq!(r.push(0, SRC, f), Ok(None));
+        }
+        assert_eq!(r.push(0, SRC, last), Ok(Some(&p[..])));
+        r.expire(0);
+        assert_eq!(r.pending(), 0);
+    }
+
+    #[test]
+    fn test_reassemble_out_of_order_interleaved() {
+        let p1 = payload(1000);
+        let p2 = payload(300);
+        let mut r = Reassembler::<2, 4096>::new(1000);
+        let mut f1: Vec<_> = fragment(1, &p1).unwrap().collect();
+        let f2: Vec<_> = fragment(1, &p2).unwrap().collect();
+        f1.reverse();
+        let other = [0x0a; 6];
+        assert_eq!(r.push(0, SRC, &f1[0]), Ok(None));
+        assert_eq!(r.push(0, other, &f2[0]), Ok(None));
+        for f in &f1[1..f1.len() - 1] {
+            assert_eq!(r.push(0, SRC, f), Ok(None));
+        }
+        assert_eq!(r.push(0, SRC, &f1[f1.len() - 1]), Ok(Some(&p1[..])));
+        assert_eq!(r.push(0, other, &f2[1]), Ok(Some(&p2[..])));
+    }
+
+    #[test]
+    fn test_reassemble_duplicate() {
+        let p = payload(600);
+        let mut r = Reassembler::<1, 1024>::new(1000);
+        let frags: Vec<_> = fragment(1, &p).unwrap().collect();
+        assert_eq!(r.push(0, SRC, &frags[0]), Ok(None));
+        assert_eq!(r.push(0, SRC, &frags[0]), Ok(None));
+        assert_eq!(r.push(0, SRC, &frags[1]), Ok(None));
+        assert_eq!(r.push(0, SRC, &frags[2]), Ok(Some(&p[..])));
+    }
+
+    #[test]
+    fn test_reassemble_timeout() {
+        let p = payload(600);
+        let mut r = Reassembler::<1, 1024>::new(100);
+        let frags: Vec<_> = fragment(1, &p).unwrap().collect();
+        assert_eq!(r.push(0, SRC, &frags[0]), Ok(None));
+        assert_eq!(r.push(50, SRC, &frags[1]), Ok(None));
+        // First fragment expired - message cannot complete
+        assert_eq!(r.push(150, SRC, &frags[2]), Ok(None));
+        assert_eq!(r.pending(), 1);
+    }
+
+    #[test]
+    fn test_reassemble_memory_bound() {
+        let p = payload(2000);
+        let mut r = Reassembler::<1, 1024>::new(100);
+        let frags: Vec<_> = fragment(1, &p).unwrap().collect();
+        assert_eq!(r.push(0, SRC, &frags[0]), Err(FragmentError::TooLarge));
+    }
+
+    #[test]
+    fn test_reassemble_evict_oldest() {
+        let p = payload(600);
+        let mut r = Reassembler::<1, 1024>::new(1000);
+        let f1: Vec<_> = fragment(1, &p).unwrap().collect();
+        let f2: Vec<_> = fragment(2, &p).unwrap().collect();
+        assert_eq!(r.push(0, SRC, &f1[0]), Ok(None));
+        assert_eq!(r.push(1, SRC, &f2[0]), Ok(None));
+        assert_eq!(r.push(2, SRC, &f1[1]), Ok(None));
+        assert_eq!(r.pending(), 1);
+    }
+
+    #[test]
+    fn test_not_fragment() {
+        let mut r = Reassembler::<1, 1024>::new(1000);
+        assert_eq!(r.push(0, SRC, b"hello"), Err(FragmentError::NotFragment));
+        assert_eq!(
+            r.push(0, SRC, &[FRAGMENT_TAG, 0, 0]),
+            Err(FragmentError::InvalidHeader)
+        );
+    }
+
+    #[test]
+    fn test_raw_tag_payload() {
+        // Ordinary payload that happens to look like a single fragment
+        let raw = [FRAGMENT_TAG, 1, 0, 0, 1, 5, 0, b'h', b'i'];
+        assert!(is_fragment(&raw));
+        let mut r = Reassembler::<1, 1024>::new(1000);
+        assert_eq!(r.push(0, SRC, &raw), Ok(Some(&b"hi"[..])));
+        // Sent through fragment() it is delivered intact
+        let frags: Vec<_> = fragment(2, &raw).unwrap().collect();
+        assert_eq!(frags.len(), 1);
+        assert_eq!(r.push(0, SRC, &frags[0]), Ok(Some(&raw[..])));
+    }
+}
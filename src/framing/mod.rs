@@ -0,0 +1,326 @@
+use crate::types::msg::MsgError;
+use crate::util::crc16;
+use crate::{Msg, MAX_MSG_LEN};
+
+// Stream framing :: COBS(Msg::to_slice [+ CRC16]) + 0x00 delimiter
+//
+// COBS guarantees the encoded frame contains no zero bytes, so a decoder can
+// always resynchronise on the next 0x00 regardless of where it joined the
+// stream or how a corrupted frame was damaged.
+
+/// Upper bound on an encoded frame (including checksum, COBS overhead and delimiter)
+pub const MAX_FRAME_LEN: usize = cobs::max_encoding_length(MAX_MSG_LEN + CRC_LEN) + 1;
+
+pub const FRAME_DELIMITER: u8 = 0x00;
+
+const CRC_LEN: usize = 2;
+
+/// Frame integrity check - both ends of the link must agree
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Checksum {
+    #[default]
+    None,
+    /// CRC-16/CCITT-FALSE trailer (little-endian) over the encoded Msg
+    Crc16,
+}
+
+/// Encode Msg as a delimited frame into buf
+pub fn encode_frame<'a>(msg: &Msg, buf: &'a mut [u8]) -> Result<&'a mut [u8], MsgError> {
+    encode_frame_with(msg, Checksum::None, buf)
+}
+
+/// Encode Msg as a delimited frame with optional checksum trailer into buf
+pub fn encode_frame_with<'a>(
+    msg: &Msg,
+    checksum: Checksum,
+    buf: &'a mut [u8],
+) -> Result<&'a mut [u8], MsgError> {
+    let mut scratch = [0_u8; MAX_MSG_LEN + CRC_LEN];
+    let mut len = msg.to_slice(&mut scratch[..MAX_MSG_LEN])?.len();
+    if checksum == Checksum::Crc16 {
+        let crc = crc16(&scratch[..len]);
+        scratch[len..len + CRC_LEN].copy_from_slice(&crc.to_le_bytes());
+        len += CRC_LEN;
+    }
+    let n = cobs::try_encode(&scratch[..len], buf).map_err(|_| MsgError::CapacityError)?;
+    let end = buf.get_mut(n).ok_or(MsgError::CapacityError)?;
+    *end = FRAME_DELIMITER;
+    Ok(&mut buf[..n + 1])
+}
+
+/// Encode Msg as a delimited frame into heapless::Vec
+pub fn encode_frame_heapless<const N: usize>(
+    msg: &Msg,
+    checksum: Checksum,
+) -> Result<heapless::Vec<u8, N>, MsgError> {
+    let mut buf = [0_u8; N];
+    let frame = encode_frame_with(msg, checksum, &mut buf)?;
+    heapless::Vec::<u8, N>::from_slice(frame).map_err(|_| MsgError::CapacityError)
+}
+
+/// Decode a single COBS frame (without delimiter) in place
+pub fn decode_frame(frame: &mut [u8]) -> Result<Msg, MsgError> {
+    decode_frame_with(frame, Checksum::None)
+}
+
+/// Decode a single COBS frame (without delimiter) in place, verifying checksum
+pub fn decode_frame_with(frame: &mut [u8], checksum: Checksum) -> Result<Msg, MsgError> {
+    let mut n = cobs::decode_in_place(frame).map_err(|_| MsgError::FramingError)?;
+    if checksum == Checksum::Crc16 {
+        n = n.checked_sub(CRC_LEN).ok_or(MsgError::ChecksumError)?;
+        let crc = u16::from_le_bytes([frame[n], frame[n + 1]]);
+        if crc != crc16(&frame[..n]) {
+            return Err(MsgError::ChecksumError);
+        }
+    }
+    Msg::from_slice(&frame[..n])
+}
+
+/// Incremental frame decoder
+///
+/// Accepts arbitrary chunks from a byte stream and yields each complete Msg.
+/// Frames which overflow the N byte buffer are discarded up to the next
+/// delimiter and reported as `MsgError::CapacityError`; corrupt frames are
+/// reported (`FramingError`/`ChecksumError`) and decoding resumes with the
+/// next frame.
+pub struct FrameDecoder<const N: usize = MAX_FRAME_LEN> {
+    buf: heapless::Vec<u8, N>,
+    overflow: bool,
+    checksum: Checksum,
+}
+
+impl<const N: usize> FrameDecoder<N> {
+    pub const fn new() -> Self {
+        Self::with_checksum(Checksum::None)
+    }
+
+    pub const fn with_checksum(checksum: Checksum) -> Self {
+        Self {
+            buf: heapless::Vec::new(),
+            overflow: false,
+            checksum,
+        }
+    }
+
+    /// Feed chunk of stream data - returns iterator over decoded frames
+    pub fn feed<'d, 'a>(&'d mut self, data: &'a [u8]) -> Frames<'d, 'a, N> {
+        Frames {
+            decoder: self,
+            data,
+        }
+    }
+
+    /// Consume data up to the end of the next complete frame
+    ///
+    /// Returns the number of bytes consumed and the decoded frame (if any) -
+    /// if no frame was completed all of data has been buffered.
+    pub fn push(&mut self, data: &[u8]) -> (usize, Option<Result<Msg, MsgError>>) {
+        let mut consumed = 0;
+        while let Some(i) = data[consumed..].iter().position(|&b| b == FRAME_DELIMITER) {
+            self.extend(&data[consumed..consumed + i]);
+            consumed += i + 1;
+            if let Some(result) = self.complete() {
+                return (consumed, Some(result));
+            }
+        }
+        // Partial frame - buffer until next chunk
+        self.extend(&data[consumed..]);
+        (data.len(), None)
+    }
+
+    /// Discard any partially received frame
+    pub fn reset(&mut self) {
+        self.buf.clear();
+        self.overflow = false;
+    }
+
+    fn extend(&mut self, data: &[u8]) {
+        if !self.overflow && self.buf.extend_from_slice(data).is_err() {
+            self.buf.clear();
+            self.overflow = true;
+        }
+    }
+
+    // Called on delimiter - empty frames are skipped
+    fn complete(&mut self) -> Option<Result<Msg, MsgError>> {
+        let result = if self.overflow {
+            Some(Err(MsgError::CapacityError))
+        } else if self.buf.is_empty() {
+            None
+        } else {
+            Some(decode_frame_with(&mut self.buf, self.checksum))
+        };
+        self.reset();
+        result
+    }
+}
+
+impl<const N: usize> Default for FrameDecoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator over frames completed by a chunk of stream data
+pub struct Frames<'d, 'a, const N: usize> {
+    decoder: &'d mut FrameDecoder<N>,
+    data: &'a [u8],
+}
+
+impl<const N: usize> Iterator for Frames<'_, '_, N> {
+    type Item = Result<Msg, MsgError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (n, result) = self.decoder.push(self.data);
+        self.data = &self.data[n..];
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn rx(id: u32, data: &[u8]) -> Msg {
+        Msg::Recv(RxData {
+            id,
+            src_addr: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+            dst_addr: [0xff; 6],
+            data: heapless::Vec::from_slice(data).unwrap(),
+            rssi: -50,
+            meta: None,
+        })
+    }
+
+    fn stream(msgs: &[Msg]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for m in msgs {
+            let mut buf = [0_u8; MAX_FRAME_LEN];
+            out.extend_from_slice(encode_frame(m, &mut buf).unwrap());
+        }
+        out
+    }
+
+    #[test]
+    fn test_max_msg_len() {
+        let m = Msg::Recv(RxData {
+            id: u32::MAX,
+            src_addr: [0xff; 6],
+            dst_addr: [0xff; 6],
+            data: heapless::Vec::from_slice(&[0xff; MAX_DATA_LEN]).unwrap(),
+            rssi: i32::MIN,
+            meta: None,
+        });
+        let mut buf = [0_u8; MAX_FRAME_LEN];
+        let frame = encode_frame(&m, &mut buf).unwrap();
+        assert!(frame.len() <= MAX_FRAME_LEN);
+    }
+
+    #[test]
+    fn test_frame_has_single_delimiter() {
+        let m = rx(0, &[0, 0, 0, 1, 0]);
+        let mut buf = [0_u8; MAX_FRAME_LEN];
+        let frame = encode_frame(&m, &mut buf).unwrap();
+        assert_eq!(frame.last(), Some(&FRAME_DELIMITER));
+        assert!(!frame[..frame.len() - 1].contains(&FRAME_DELIMITER));
+    }
+
+    #[test]
+    fn test_decode_single_chunk() {
+        let msgs = [rx(1, b"one"), rx(2, b"two"), rx(3, b"three")];
+        let mut decoder = FrameDecoder::<MAX_FRAME_LEN>::new();
+        let out: Vec<_> = decoder.feed(&stream(&msgs)).collect();
+        assert_eq!(out, msgs.map(Ok));
+    }
+
+    #[test]
+    fn test_decode_byte_at_a_time() {
+        let msgs = [rx(1, b"one"), rx(2, &[0; 40]), rx(3, b"three")];
+        let mut decoder = FrameDecoder::<MAX_FRAME_LEN>::new();
+        let mut out = Vec::new();
+        for b in stream(&msgs) {
+            out.extend(decoder.feed(&[b]));
+        }
+        assert_eq!(out, msgs.map(Ok));
+    }
+
+    #[test]
+    fn test_decode_resync_mid_frame() {
+        let msgs = [rx(1, b"one"), rx(2, b"two")];
+        let s = stream(&msgs);
+        let mut decoder = FrameDecoder::<MAX_FRAME_LEN>::new();
+        // Join stream part way through first frame
+        let out: Vec<_> = decoder.feed(&s[3..]).collect();
+        assert_eq!(out.len(), 2);
+        assert!(out[0].is_err());
+        assert_eq!(out[1], Ok(msgs[1].clone()));
+    }
+
+    #[test]
+    fn test_decode_overflow() {
+        let msgs = [rx(1, &[0xaa; 200]), rx(2, b"two")];
+        let mut decoder = FrameDecoder::<64>::new();
+        let out: Vec<_> = decoder.feed(&stream(&msgs)).collect();
+        assert_eq!(out, [Err(MsgError::CapacityError), Ok(msgs[1].clone())]);
+    }
+
+    #[test]
+    fn test_crc_roundtrip() {
+        let msgs = [rx(1, b"one"), rx(2, &[0; 40])];
+        let mut s = Vec::new();
+        for m in &msgs {
+            let frame = encode_frame_heapless::<MAX_FRAME_LEN>(m, Checksum::Crc16).unwrap();
+            s.extend_from_slice(&frame);
+        }
+        let mut decoder = FrameDecoder::<MAX_FRAME_LEN>::with_checksum(Checksum::Crc16);
+        let out: Vec<_> = decoder.feed(&s).collect();
+        assert_eq!(out, msgs.map(Ok));
+    }
+
+    #[test]
+    fn test_crc_corrupt_frame_resync() {
+        let msgs = [rx(1, b"one"), rx(2, b"two"), rx(3, b"three")];
+        let frames: Vec<_> = msgs
+            .iter()
+            .map(|m| encode_frame_heapless::<MAX_FRAME_LEN>(m, Checksum::Crc16).unwrap())
+            .collect();
+        // Flip a single bit in the payload of the second frame
+        let mut corrupt = frames[1].clone();
+        corrupt[12] ^= 0x04;
+        assert_ne!(corrupt[12], FRAME_DELIMITER);
+        let mut s = Vec::new();
+        s.extend_from_slice(&frames[0]);
+        s.extend_from_slice(&corrupt);
+        s.extend_from_slice(&frames[2]);
+        let mut decoder = FrameDecoder::<MAX_FRAME_LEN>::with_checksum(Checksum::Crc16);
+        let out: Vec<_> = decoder.feed(&s).collect();
+        assert_eq!(
+            out,
+            [
+                Ok(msgs[0].clone()),
+                Err(MsgError::ChecksumError),
+                Ok(msgs[2].clone())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_crc_short_frame() {
+        let mut frame = [0x02, 0x01];
+        assert_eq!(
+            decode_frame_with(&mut frame, Checksum::Crc16),
+            Err(MsgError::ChecksumError)
+        );
+    }
+
+    #[test]
+    fn test_skip_empty_frames() {
+        let msgs = [rx(1, b"one")];
+        let mut s = vec![0, 0];
+        s.extend(stream(&msgs));
+        let mut decoder = FrameDecoder::<MAX_FRAME_LEN>::new();
+        let out: Vec<_> = decoder.feed(&s).collect();
+        assert_eq!(out, msgs.map(Ok));
+    }
+}
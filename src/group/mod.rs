@@ -0,0 +1,250 @@
+use crate::types::{GroupStatus, MemberResult, SendGroup, MAX_GROUPS, MAX_GROUP_MEMBERS};
+use crate::{AckError, TxData};
+
+// Hub side peer groups
+//
+// Reference implementation of AddToGroup/RemoveFromGroup/SendGroup:
+//
+// >>> Msg::AddToGroup(m) => groups.add(m.group, &m.address),  // Ack
+// >>> Msg::RemoveFromGroup(m) => groups.remove(m.group, &m.address),  // Ack
+// >>> Msg::RemovePeer(p) => groups.remove_peer(&p.address),
+// >>> Msg::SendGroup(s) => {
+// >>>     let (mut send, tx) = groups.send(&s, next_id(), || next_id())?;  // Ack
+// >>>     for tx in tx { send.sent(&tx); espnow.send(tx) }
+// >>> }
+// >>> ...
+// >>> // ESP-NOW send callback
+// >>> if let Some(status) = send.result(tx_id, success) { Msg::GroupStatus(status) }
+
+#[derive(Clone, Debug)]
+struct Group {
+    group: u8,
+    members: heapless::Vec<[u8; 6], MAX_GROUP_MEMBERS>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GroupTable<const N: usize = MAX_GROUPS> {
+    groups: heapless::Vec<Group, N>,
+}
+
+impl<const N: usize> GroupTable<N> {
+    pub const fn new() -> Self {
+        Self {
+            groups: heapless::Vec::new(),
+        }
+    }
+
+    fn position(&self, group: u8) -> Option<usize> {
+        self.groups.iter().position(|g| g.group == group)
+    }
+
+    pub fn add(&mut self, group: u8, address: &[u8; 6]) -> Result<(), AckError> {
+        let i = match self.position(group) {
+            Some(i) => i,
+            None => {
+                self.groups
+                    .push(Group {
+                        group,
+                        members: heapless::Vec::new(),
+                    })
+                    .map_err(|_| AckError::GroupFull)?;
+                self.groups.len() - 1
+            }
+        };
+        let members = &mut self.groups[i].members;
+        if !members.contains(address) {
+            members.push(*address).map_err(|_| AckError::GroupFull)?;
+        }
+        Ok(())
+    }
+
+    /// Remove member (empty groups are deleted)
+    pub fn remove(&mut self, group: u8, address: &[u8; 6]) -> Result<(), AckError> {
+        let i = self.position(group).ok_or(AckError::GroupNotFound)?;
+        let members = &mut self.groups[i].members;
+        let m = members
+            .iter()
+            .position(|a| a == address)
+            .ok_or(AckError::GroupNotFound)?;
+        members.remove(m);
+        if members.is_empty() {
+            self.groups.remove(i);
+        }
+        Ok(())
+    }
+
+    /// Remove peer from all groups
+    pub fn remove_peer(&mut self, address: &[u8; 6]) {
+        self.groups
+            .iter_mut()
+            .for_each(|g| g.members.retain(|a| a != address));
+        self.groups.retain(|g| !g.members.is_empty());
+    }
+
+    pub fn members(&self, group: u8) -> &[[u8; 6]] {
+        self.position(group)
+            .map(|i| self.groups[i].members.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Groups containing address
+    pub fn groups_of<'a>(&'a self, address: &'a [u8; 6]) -> impl Iterator<Item = u8> + 'a {
+        self.groups
+            .iter()
+            .filter(|g| g.members.contains(address))
+            .map(|g| g.group)
+    }
+
+    /// Fan out SendGroup - returns the result tracker (status_id is the
+    /// GroupStatus id) and a TxData per member (record each with
+    /// GroupSend::sent)
+    pub fn send<'a>(
+        &'a self,
+        send: &'a SendGroup,
+        status_id: u32,
+        next_id: impl FnMut() -> u32 + 'a,
+    ) -> Result<(GroupSend, impl Iterator<Item = TxData> + 'a), AckError> {
+        let members = self.members(send.group);
+        if members.is_empty() {
+            return Err(AckError::GroupNotFound);
+        }
+        Ok((
+            GroupSend::new(status_id, send, members.len()),
+            send.fan_out(members, next_id),
+        ))
+    }
+}
+
+/// Per-member results for a single SendGroup
+#[derive(Clone, Debug)]
+pub struct GroupSend {
+    status: GroupStatus,
+    /// (tx_id, result index) waiting for send callback
+    pending: heapless::Vec<(u32, usize), MAX_GROUP_MEMBERS>,
+    members: usize,
+}
+
+impl GroupSend {
+    pub fn new(status_id: u32, send: &SendGroup, members: usize) -> Self {
+        Self {
+            status: GroupStatus {
+                id: status_id,
+                rx_id: send.id,
+                group: send.group,
+                results: heapless::Vec::new(),
+            },
+            pending: heapless::Vec::new(),
+            members: members.min(MAX_GROUP_MEMBERS),
+        }
+    }
+
+    /// Record TxData sent to member
+    pub fn sent(&mut self, tx: &TxData) {
+        let i = self.status.results.len();
+        if self
+            .status
+            .results
+            .push(MemberResult {
+                address: tx.dst_addr,
+                success: false,
+            })
+            .is_ok()
+        {
+            let _ = self.pending.push((tx.id, i));
+        }
+    }
+
+    /// Record send result - returns GroupStatus when all members are done
+    pub fn result(&mut self, tx_id: u32, success: bool) -> Option<GroupStatus> {
+        let p = self.pending.iter().position(|(id, _)| *id == tx_id)?;
+        let (_, i) = self.pending.remove(p);
+        self.status.results[i].success = success;
+        self.is_complete().then(|| self.status.clone())
+    }
+
+    /// All members sent and results received
+    pub fn is_complete(&self) -> bool {
+        self.status.results.len() == self.members && self.pending.is_empty()
+    }
+
+    pub fn rx_id(&self) -> u32 {
+        self.status.rx_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{A, B};
+
+    fn send(group: u8) -> SendGroup {
+        SendGroup {
+            id: 100,
+            group,
+            data: heapless::Vec::from_slice(b"group").unwrap(),
+            defer: false,
+        }
+    }
+
+    #[test]
+    fn test_members() {
+        let mut g = GroupTable::<2>::new();
+        g.add(1, &A).unwrap();
+        g.add(1, &A).unwrap();
+        g.add(1, &B).unwrap();
+        g.add(2, &B).unwrap();
+        assert_eq!(g.add(3, &A), Err(AckError::GroupFull));
+        assert_eq!(g.members(1), &[A, B]);
+        assert_eq!(g.groups_of(&B).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(g.remove(2, &A), Err(AckError::GroupNotFound));
+        assert_eq!(g.remove(3, &A), Err(AckError::GroupNotFound));
+        g.remove(2, &B).unwrap();
+        // Empty group is deleted
+        g.add(3, &A).unwrap();
+        g.remove_peer(&A);
+        assert_eq!(g.members(1), &[B]);
+        assert!(g.members(3).is_empty());
+    }
+
+    #[test]
+    fn test_send() {
+        let mut g = GroupTable::<2>::new();
+        g.add(1, &A).unwrap();
+        g.add(1, &B).unwrap();
+        assert!(matches!(
+            g.send(&send(2), 1, || 0),
+            Err(AckError::GroupNotFound)
+        ));
+        let s = send(1);
+        let mut id = 10;
+        let (mut tracker, tx) = g
+            .send(&s, 1, || {
+                id += 1;
+                id
+            })
+            .unwrap();
+        let tx = tx.collect::<Vec<_>>();
+        // Result before all members are sent
+        tracker.sent(&tx[0]);
+        assert_eq!(tracker.result(tx[0].id, true), None);
+        tracker.sent(&tx[1]);
+        assert_eq!(tracker.result(99, true), None);
+        assert!(!tracker.is_complete());
+        let status = tracker.result(tx[1].id, false).unwrap();
+        assert_eq!((status.id, status.rx_id, status.group), (1, 100, 1));
+        assert_eq!(
+            status.results.as_slice(),
+            &[
+                MemberResult {
+                    address: A,
+                    success: true
+                },
+                MemberResult {
+                    address: B,
+                    success: false
+                }
+            ]
+        );
+        assert!(tracker.is_complete());
+    }
+}
@@ -0,0 +1,279 @@
+use crate::{AckError, InitConfig, Msg, MAX_DATA_LEN, MAX_DATA_LEN_V1, MAX_DATA_LEN_V2, VERSION};
+
+use core::fmt::Display;
+use core::ops::RangeInclusive;
+
+// Hub -> Server :: Init handshake
+//
+// The hub reports its protocol (api_version) and ESP-NOW (now_version)
+// versions in InitConfig. The server checks these against the range it
+// supports before sending any other messages:
+//
+// >>> match policy.check(&init) {
+// >>>     Compatibility::Reject(reason) => ... drop hub ...
+// >>>     c => if c.allows(&msg) { send(msg) }
+// >>> }
+
+/// Oldest hub api_version the server can talk to
+///
+/// Version 0 encoded Msg as a bare postcard enum, which cannot be decoded
+/// since the wire envelope was introduced in version 1.
+pub const MIN_API_VERSION: u32 = 1;
+
+/// Hub versions accepted by the server
+///
+/// The top of the api_version range is the version the server speaks - hubs
+/// running older versions are accepted in degraded mode.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VersionPolicy {
+    pub api_version: RangeInclusive<u32>,
+    pub now_version: RangeInclusive<u32>,
+}
+
+impl VersionPolicy {
+    pub const fn new(api_version: RangeInclusive<u32>, now_version: RangeInclusive<u32>) -> Self {
+        Self {
+            api_version,
+            now_version,
+        }
+    }
+
+    pub fn check(&self, init: &InitConfig) -> Compatibility {
+        if !self.api_version.contains(&init.api_version) {
+            Compatibility::Reject(RejectReason::ApiVersion(init.api_version))
+        } else if !self.now_version.contains(&init.now_version) {
+            Compatibility::Reject(RejectReason::NowVersion(init.now_version))
+        } else if init.api_version < *self.api_version.end() {
+            Compatibility::Degrade {
+                api_version: init.api_version,
+            }
+        } else {
+            Compatibility::Compatible
+        }
+    }
+}
+
+impl Default for VersionPolicy {
+    fn default() -> Self {
+        // ESP-NOW v1 & v2
+        Self::new(MIN_API_VERSION..=VERSION, 1..=2)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum Compatibility {
+    Compatible,
+    /// Hub runs an older api_version - only messages it understands may be sent
+    Degrade {
+        api_version: u32,
+    },
+    Reject(RejectReason),
+}
+
+impl Compatibility {
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, Compatibility::Reject(_))
+    }
+
+    /// Check whether msg can be sent to the hub
+    pub fn allows(&self, msg: &Msg) -> bool {
+        match self {
+            Compatibility::Compatible => true,
+            Compatibility::Degrade { api_version } => msg.api_version() <= *api_version,
+            Compatibility::Reject(_) => false,
+        }
+    }
+}
+
+impl Display for Compatibility {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Compatibility::Compatible => write!(f, "Compatible"),
+            Compatibility::Degrade { api_version } => {
+                write!(f, "Degrade: api_version={}", api_version)
+            }
+            Compatibility::Reject(r) => write!(f, "Reject: {}", r),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum RejectReason {
+    ApiVersion(u32),
+    NowVersion(u32),
+}
+
+impl Display for RejectReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RejectReason::ApiVersion(v) => write!(f, "unsupported api_version={}", v),
+            RejectReason::NowVersion(v) => write!(f, "unsupported now_version={}", v),
+        }
+    }
+}
+
+/// Maximum ESP-NOW payload supported by a hub running now_version
+///
+/// Limited to the local MAX_DATA_LEN capacity (see `espnow-v2` feature)
+pub fn max_data_len(now_version: u32) -> usize {
+    if now_version >= 2 {
+        MAX_DATA_LEN_V2.min(MAX_DATA_LEN)
+    } else {
+        MAX_DATA_LEN_V1
+    }
+}
+
+/// Check msg payload fits the hub's ESP-NOW version
+pub fn check_payload(init: &InitConfig, msg: &Msg) -> Result<(), AckError> {
+    match msg.data_len() {
+        Some(len) if len > max_data_len(init.now_version) => Err(AckError::Capacity),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init(api_version: u32, now_version: u32) -> InitConfig {
+        InitConfig {
+            id: 0,
+            api_version,
+            now_version,
+            channel: 1,
+            address: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+        }
+    }
+
+    #[test]
+    fn test_compatible() {
+        let c = VersionPolicy::default().check(&init(VERSION, 1));
+        assert_eq!(c, Compatibility::Compatible);
+        assert!(c.allows(&Msg::Init(init(VERSION, 1))));
+    }
+
+    #[test]
+    fn test_reject_api_version() {
+        let c = VersionPolicy::default().check(&init(VERSION + 1, 1));
+        assert_eq!(
+            c,
+            Compatibility::Reject(RejectReason::ApiVersion(VERSION + 1))
+        );
+        assert!(!c.allows(&Msg::Init(init(VERSION, 1))));
+    }
+
+    #[test]
+    fn test_reject_now_version() {
+        let c = VersionPolicy::default().check(&init(VERSION, 3));
+        assert_eq!(c, Compatibility::Reject(RejectReason::NowVersion(3)));
+    }
+
+    #[test]
+    fn test_max_data_len() {
+        assert_eq!(max_data_len(1), MAX_DATA_LEN_V1);
+        assert_eq!(max_data_len(2), MAX_DATA_LEN);
+    }
+
+    #[test]
+    fn test_check_payload() {
+        let tx = |len| {
+            Msg::Send(crate::TxData {
+                id: 1,
+                dst_addr: [0xff; 6],
+                data: heapless::Vec::from_slice(&[0; MAX_DATA_LEN][..len]).unwrap(),
+                defer: false,
+                schedule: None,
+            })
+        };
+        assert_eq!(
+            check_payload(&init(VERSION, 1), &tx(MAX_DATA_LEN_V1)),
+            Ok(())
+        );
+        assert_eq!(check_payload(&init(VERSION, 2), &tx(MAX_DATA_LEN)), Ok(()));
+        if MAX_DATA_LEN > MAX_DATA_LEN_V1 {
+            assert_eq!(
+                check_payload(&init(VERSION, 1), &tx(MAX_DATA_LEN_V1 + 1)),
+                Err(AckError::Capacity)
+            );
+        }
+        assert_eq!(
+            check_payload(&init(VERSION, 1), &Msg::Ack(crate::Ack::ok(1, 2))),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_degrade() {
+        let policy = VersionPolicy::new(0..=VERSION + 1, 1..=2);
+        let c = policy.check(&init(VERSION, 1));
+        assert_eq!(
+            c,
+            Compatibility::Degrade {
+                api_version: VERSION
+            }
+        );
+        assert!(c.allows(&Msg::Init(init(VERSION, 1))));
+    }
+
+    #[test]
+    fn test_degrade_trailer() {
+        let c = Compatibility::Degrade { api_version: 1 };
+        let config = crate::HubConfig {
+            id: 1,
+            channel: None,
+            pmk: None,
+            wake_window: None,
+            rate: None,
+            rx_meta: None,
+        };
+        assert!(c.allows(&Msg::HubConfig(config.clone())));
+        // Api 1 hub would ignore the rx_meta setting
+        assert!(!c.allows(&Msg::HubConfig(crate::HubConfig {
+            rx_meta: Some(true),
+            ..config
+        })));
+        let beacon = crate::BroadcastData {
+            id: 2,
+            data: heapless::Vec::from_slice(b"beacon").unwrap(),
+            interval: Some(1000),
+            replace: None,
+        };
+        assert!(c.allows(&Msg::Broadcast(beacon.clone())));
+        // Api 1 hub would start a second broadcast
+        assert!(!c.allows(&Msg::Broadcast(crate::BroadcastData {
+            replace: Some(1),
+            ..beacon
+        })));
+        let tx = crate::TxData {
+            id: 3,
+            dst_addr: [0xff; 6],
+            data: heapless::Vec::from_slice(b"later").unwrap(),
+            defer: false,
+            schedule: None,
+        };
+        assert!(c.allows(&Msg::Send(tx.clone())));
+        // Api 1 hub would send immediately
+        assert!(!c.allows(&Msg::Send(crate::TxData {
+            schedule: Some(crate::Schedule::delay(1000)),
+            ..tx
+        })));
+    }
+
+    #[test]
+    fn test_reject_pre_envelope() {
+        let c = VersionPolicy::default().check(&init(0, 1));
+        assert_eq!(c, Compatibility::Reject(RejectReason::ApiVersion(0)));
+    }
+
+    #[test]
+    fn test_degrade_ping() {
+        let c = VersionPolicy::default().check(&init(1, 1));
+        assert_eq!(c, Compatibility::Degrade { api_version: 1 });
+        let ping = Msg::Ping(crate::Ping {
+            id: 1,
+            timestamp: 0,
+        });
+        assert!(!c.allows(&ping));
+        assert!(c.allows(&Msg::Ack(crate::Ack::ok(1, 2))));
+    }
+}
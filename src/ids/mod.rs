@@ -0,0 +1,217 @@
+// Message id allocation and duplicate detection
+//
+// Ids are split into a per-session epoch (top 8 bits) and a 24-bit sequence
+// number which wraps within the epoch. Start each session (connect/reconnect)
+// with a new epoch so ids from an earlier session are never mistaken for new
+// messages:
+//
+// >>> let mut ids = IdAllocator::new(epoch);
+// >>> let tx = TxData { id: ids.next_id(), ... };
+// >>> ...
+// >>> // Receiver (check AckCache first so retransmissions are re-acked)
+// >>> if !window.check(msg.get_id()) { continue }  // duplicate
+
+pub const EPOCH_BITS: u32 = 8;
+pub const SEQ_BITS: u32 = 32 - EPOCH_BITS;
+pub const SEQ_MASK: u32 = (1 << SEQ_BITS) - 1;
+/// Ids tracked behind the newest id seen
+pub const DUPLICATE_WINDOW: u32 = 64;
+
+pub const fn epoch_of(id: u32) -> u8 {
+    (id >> SEQ_BITS) as u8
+}
+
+pub const fn seq_of(id: u32) -> u32 {
+    id & SEQ_MASK
+}
+
+pub const fn make_id(epoch: u8, seq: u32) -> u32 {
+    ((epoch as u32) << SEQ_BITS) | (seq & SEQ_MASK)
+}
+
+/// Serial number comparison (RFC 1982) - true if a is after b
+fn seq_after(a: u32, b: u32, bits: u32) -> bool {
+    let mask = (1_u64 << bits) as u32 - 1;
+    let diff = a.wrapping_sub(b) & mask;
+    diff != 0 && diff < (1 << (bits - 1))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct IdAllocator {
+    epoch: u8,
+    seq: u32,
+}
+
+impl IdAllocator {
+    pub const fn new(epoch: u8) -> Self {
+        Self { epoch, seq: 0 }
+    }
+
+    /// Continue from id (eg. restored state)
+    pub const fn from_id(id: u32) -> Self {
+        Self {
+            epoch: epoch_of(id),
+            seq: seq_of(id),
+        }
+    }
+
+    /// Allocate id (sequence wraps within the epoch)
+    pub fn next_id(&mut self) -> u32 {
+        let id = make_id(self.epoch, self.seq);
+        self.seq = (self.seq + 1) & SEQ_MASK;
+        id
+    }
+
+    /// Start new session - returns the new epoch
+    pub fn next_epoch(&mut self) -> u8 {
+        self.epoch = self.epoch.wrapping_add(1);
+        self.seq = 0;
+        self.epoch
+    }
+
+    pub fn epoch(&self) -> u8 {
+        self.epoch
+    }
+}
+
+/// Sliding window duplicate detector
+///
+/// Tracks the newest id seen and which of the previous DUPLICATE_WINDOW ids
+/// have been received. Ids older than the window, or from an earlier epoch,
+/// are treated as duplicates.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DuplicateWindow {
+    /// Newest id seen (None before the first id)
+    newest: Option<u32>,
+    /// Bit n set if id newest - n has been seen
+    seen: u64,
+}
+
+impl DuplicateWindow {
+    pub const fn new() -> Self {
+        Self {
+            newest: None,
+            seen: 0,
+        }
+    }
+
+    /// Record id - returns false if it is a duplicate
+    pub fn check(&mut self, id: u32) -> bool {
+        let Some(newest) = self.newest else {
+            self.accept(id, 0);
+            return true;
+        };
+        let (epoch, newest_epoch) = (epoch_of(id), epoch_of(newest));
+        if epoch != newest_epoch {
+            // New session resets the window
+            if seq_after(epoch as u32, newest_epoch as u32, EPOCH_BITS) {
+                self.accept(id, 0);
+                return true;
+            }
+            return false;
+        }
+        let (seq, newest_seq) = (seq_of(id), seq_of(newest));
+        if seq_after(seq, newest_seq, SEQ_BITS) {
+            let shift = seq.wrapping_sub(newest_seq) & SEQ_MASK;
+            let seen = self.seen.checked_shl(shift).unwrap_or(0);
+            self.accept(id, seen);
+            return true;
+        }
+        let age = newest_seq.wrapping_sub(seq) & SEQ_MASK;
+        if age >= DUPLICATE_WINDOW || self.seen & (1 << age) != 0 {
+            return false;
+        }
+        self.seen |= 1 << age;
+        true
+    }
+
+    fn accept(&mut self, id: u32, seen: u64) {
+        self.newest = Some(id);
+        self.seen = seen | 1;
+    }
+
+    /// Peek without recording id
+    pub fn is_duplicate(&self, id: u32) -> bool {
+        let mut w = *self;
+        !w.check(id)
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocator() {
+        let mut ids = IdAllocator::new(0);
+        assert_eq!((ids.next_id(), ids.next_id()), (0, 1));
+        assert_eq!(ids.next_epoch(), 1);
+        assert_eq!(ids.next_id(), 0x0100_0000);
+        // Sequence wraps within epoch
+        let mut ids = IdAllocator::from_id(u32::MAX - 1);
+        assert_eq!(ids.epoch(), 0xff);
+        assert_eq!(ids.next_id(), u32::MAX - 1);
+        assert_eq!(ids.next_id(), u32::MAX);
+        assert_eq!(ids.next_id(), 0xff00_0000);
+        // Epoch wraps
+        assert_eq!(ids.next_epoch(), 0);
+        assert_eq!(ids.next_id(), 0);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut w = DuplicateWindow::new();
+        assert!(w.check(10));
+        assert!(!w.check(10));
+        assert!(w.check(12));
+        // Out of order within window
+        assert!(w.check(11));
+        assert!(!w.check(11));
+        assert!(w.check(5));
+        assert!(w.is_duplicate(12));
+        assert!(!w.is_duplicate(13));
+        // Too old
+        assert!(w.check(12 + DUPLICATE_WINDOW));
+        assert!(!w.check(12));
+        assert!(w.check(13));
+        // Jump beyond window
+        assert!(w.check(1000));
+        assert!(!w.check(13 + DUPLICATE_WINDOW));
+        w.reset();
+        assert!(w.check(12));
+    }
+
+    #[test]
+    fn test_wrap() {
+        let mut ids = IdAllocator::from_id(u32::MAX - 2);
+        let mut w = DuplicateWindow::new();
+        let sent = (0..5).map(|_| ids.next_id()).collect::<Vec<_>>();
+        assert_eq!(sent[2], u32::MAX);
+        assert_eq!(sent[3], 0xff00_0000);
+        for id in &sent {
+            assert!(w.check(*id));
+        }
+        // Ids before the wrap are still tracked
+        assert!(!w.check(u32::MAX));
+        assert!(!w.check(u32::MAX - 1));
+        assert!(w.is_duplicate(0xff00_0001));
+        assert!(!w.is_duplicate(0xff00_0002));
+    }
+
+    #[test]
+    fn test_epoch() {
+        let mut w = DuplicateWindow::new();
+        assert!(w.check(make_id(0xff, 100)));
+        // New session (epoch wraps to 0) resets window
+        assert!(w.check(make_id(0, 0)));
+        assert!(w.check(make_id(0, 100)));
+        // Replay from earlier session
+        assert!(!w.check(make_id(0xff, 101)));
+        assert!(!w.check(make_id(0, 0)));
+        assert!(w.check(make_id(0, 50)));
+    }
+}
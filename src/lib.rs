@@ -1,6 +1,26 @@
 #![cfg_attr(not(any(test, feature = "std")), no_std)]
 
+#[cfg(feature = "tokio")]
+pub mod client;
+#[cfg(feature = "tokio")]
+pub mod codec;
+pub mod deferred;
+#[cfg(feature = "tokio")]
+pub mod delivery;
+pub mod fragment;
+pub mod framing;
+pub mod group;
+pub mod handshake;
+pub mod ids;
+#[cfg(feature = "std")]
+pub mod liveness;
 pub mod monitor;
+pub mod periodic;
+pub mod registry;
+pub mod retry;
+pub mod scheduler;
+#[cfg(feature = "embedded-io")]
+pub mod transport;
 pub mod types;
 pub mod util;
 
@@ -13,5 +33,32 @@ pub use util::register_espnow;
 #[cfg(test)]
 mod tests;
 
-pub const VERSION: u32 = 0;
-pub const MAX_DATA_LEN: usize = 250;
+/// Protocol api_version (1: wire envelope, 2: Ping/Pong and later messages)
+pub const VERSION: u32 = 2;
+/// ESP-NOW v1 payload limit
+pub const MAX_DATA_LEN_V1: usize = 250;
+/// ESP-NOW v2 payload limit (ESP-IDF 5.4+)
+pub const MAX_DATA_LEN_V2: usize = 1470;
+/// Payload capacity of TxData/RxData/BroadcastData (set by espnow-v2 feature)
+///
+/// The wire encoding is length-prefixed so the capacity does not affect
+/// compatibility - a v1 build rejects payloads over MAX_DATA_LEN_V1.
+#[cfg(not(feature = "espnow-v2"))]
+pub const MAX_DATA_LEN: usize = MAX_DATA_LEN_V1;
+#[cfg(feature = "espnow-v2")]
+pub const MAX_DATA_LEN: usize = MAX_DATA_LEN_V2;
+/// Upper bound on the wire encoding of a Msg
+///
+/// Envelope header plus the largest body - RxData with a full payload and
+/// RxMeta, or a PeerList page when that is larger (ESP-NOW v1). Hubs send
+/// PeerList entries with LMKs redacted, but the bound allows for them so any
+/// PeerList value can be encoded.
+pub const MAX_MSG_LEN: usize = {
+    use types::msg::{ENVELOPE_HEADER_LEN, PEER_LIST_MAX_LEN, RX_DATA_MAX_LEN};
+    ENVELOPE_HEADER_LEN
+        + if RX_DATA_MAX_LEN > PEER_LIST_MAX_LEN {
+            RX_DATA_MAX_LEN
+        } else {
+            PEER_LIST_MAX_LEN
+        }
+};
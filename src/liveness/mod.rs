@@ -0,0 +1,213 @@
+use crate::{Ping, Pong};
+
+use core::fmt::Display;
+use std::time::{Duration, Instant};
+
+// Link liveness :: send Ping every interval and track the matching Pong
+//
+// The link is declared dead after max_missed consecutive pings go
+// unanswered. The caller owns the clock (passes `now`) and the transport:
+//
+// >>> if liveness.ping_due(Instant::now()) {
+// >>>     send(Msg::Ping(liveness.ping(next_id(), Instant::now())))
+// >>> }
+// >>> ...
+// >>> Msg::Pong(p) => { liveness.pong(&p, Instant::now()); }
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum LinkState {
+    /// No pong received yet
+    Unknown,
+    Alive,
+    Dead,
+}
+
+impl Display for LinkState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s = match self {
+            LinkState::Unknown => "Unknown",
+            LinkState::Alive => "Alive",
+            LinkState::Dead => "Dead",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Liveness {
+    interval: Duration,
+    max_missed: u32,
+    epoch: Instant,
+    // Outstanding ping (id, sent)
+    pending: Option<(u32, Instant)>,
+    last_ping: Option<Instant>,
+    missed: u32,
+    state: LinkState,
+    rtt: Option<Duration>,
+    srtt: Option<Duration>,
+}
+
+impl Liveness {
+    pub fn new(interval: Duration, max_missed: u32) -> Self {
+        Self {
+            interval,
+            max_missed,
+            epoch: Instant::now(),
+            pending: None,
+            last_ping: None,
+            missed: 0,
+            state: LinkState::Unknown,
+            rtt: None,
+            srtt: None,
+        }
+    }
+
+    /// Check if next ping should be sent
+    pub fn ping_due(&self, now: Instant) -> bool {
+        match self.last_ping {
+            Some(t) => now.saturating_duration_since(t) >= self.interval,
+            None => true,
+        }
+    }
+
+    /// Create next Ping - an unanswered previous ping is counted as missed
+    pub fn ping(&mut self, id: u32, now: Instant) -> Ping {
+        if self.pending.is_some() {
+            self.missed = self.missed.saturating_add(1);
+            if self.missed >= self.max_missed {
+                self.state = LinkState::Dead;
+            }
+        }
+        self.pending = Some((id, now));
+        self.last_ping = Some(now);
+        Ping {
+            id,
+            timestamp: now.saturating_duration_since(self.epoch).as_millis() as u64,
+        }
+    }
+
+    /// Handle Pong - returns round-trip time if it matches the outstanding ping
+    ///
+    /// Pongs for earlier (already missed) pings are ignored.
+    pub fn pong(&mut self, pong: &Pong, now: Instant) -> Option<Duration> {
+        match self.pending {
+            Some((id, sent)) if id == pong.ping_id => {
+                let rtt = now.saturating_duration_since(sent);
+                self.pending = None;
+                self.missed = 0;
+                self.state = LinkState::Alive;
+                self.rtt = Some(rtt);
+                // Smoothed RTT (RFC 6298 - alpha = 1/8)
+                self.srtt = Some(match self.srtt {
+                    Some(srtt) => (srtt * 7 + rtt) / 8,
+                    None => rtt,
+                });
+                Some(rtt)
+            }
+            _ => None,
+        }
+    }
+
+    /// Forget link history (eg. after reconnect)
+    pub fn reset(&mut self) {
+        self.pending = None;
+        self.last_ping = None;
+        self.missed = 0;
+        self.state = LinkState::Unknown;
+        self.rtt = None;
+        self.srtt = None;
+    }
+
+    pub fn state(&self) -> LinkState {
+        self.state
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.state == LinkState::Dead
+    }
+
+    /// Consecutive unanswered pings
+    pub fn missed(&self) -> u32 {
+        self.missed
+    }
+
+    /// Last round-trip time
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    /// Smoothed round-trip time
+    pub fn srtt(&self) -> Option<Duration> {
+        self.srtt
+    }
+}
+
+impl Display for Liveness {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Liveness: state={} missed={} rtt={:?} srtt={:?}",
+            self.state, self.missed, self.rtt, self.srtt
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn test_alive() {
+        let t0 = Instant::now();
+        let mut l = Liveness::new(INTERVAL, 3);
+        assert_eq!(l.state(), LinkState::Unknown);
+        assert!(l.ping_due(t0));
+        let ping = l.ping(1, t0);
+        assert!(!l.ping_due(t0 + INTERVAL / 2));
+        let rtt = l.pong(&ping.pong(100, 0), t0 + Duration::from_millis(20));
+        assert_eq!(rtt, Some(Duration::from_millis(20)));
+        assert_eq!(l.state(), LinkState::Alive);
+        assert_eq!(l.srtt(), Some(Duration::from_millis(20)));
+        assert!(l.ping_due(t0 + INTERVAL));
+    }
+
+    #[test]
+    fn test_dead() {
+        let t0 = Instant::now();
+        let mut l = Liveness::new(INTERVAL, 2);
+        let ping = l.ping(1, t0);
+        l.pong(&ping.pong(100, 0), t0);
+        l.ping(2, t0 + INTERVAL);
+        assert_eq!(l.state(), LinkState::Alive);
+        l.ping(3, t0 + INTERVAL * 2);
+        assert_eq!((l.missed(), l.state()), (1, LinkState::Alive));
+        l.ping(4, t0 + INTERVAL * 3);
+        assert_eq!((l.missed(), l.state()), (2, LinkState::Dead));
+        assert!(l.is_dead());
+    }
+
+    #[test]
+    fn test_stale_pong() {
+        let t0 = Instant::now();
+        let mut l = Liveness::new(INTERVAL, 3);
+        let ping = l.ping(1, t0);
+        let ping2 = l.ping(2, t0 + INTERVAL);
+        assert_eq!(l.pong(&ping.pong(100, 0), t0 + INTERVAL), None);
+        assert_eq!(l.missed(), 1);
+        assert!(l.pong(&ping2.pong(101, 0), t0 + INTERVAL).is_some());
+        assert_eq!(l.missed(), 0);
+    }
+
+    #[test]
+    fn test_recover() {
+        let t0 = Instant::now();
+        let mut l = Liveness::new(INTERVAL, 1);
+        l.ping(1, t0);
+        let ping = l.ping(2, t0 + INTERVAL);
+        assert!(l.is_dead());
+        l.pong(&ping.pong(100, 0), t0 + INTERVAL);
+        assert_eq!(l.state(), LinkState::Alive);
+    }
+}
@@ -1,13 +1,14 @@
 use core::fmt::Display;
 use serde::{Deserialize, Serialize};
 
-use crate::Msg;
+use crate::types::msg::MsgError;
+use crate::{Msg, MsgRef};
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 pub enum Monitor {
     Tx(Msg),
     Rx(Msg),
-    RxError,
+    RxError(MsgError),
     TxError,
 }
 
@@ -18,8 +19,8 @@ impl Monitor {
     pub fn new_rx(msg: &Msg) -> Self {
         Self::Rx(msg.clone())
     }
-    pub fn new_rxerror() -> Self {
-        Self::RxError
+    pub fn new_rxerror(e: MsgError) -> Self {
+        Self::RxError(e)
     }
     pub fn new_txerror() -> Self {
         Self::TxError
@@ -32,7 +33,7 @@ impl Display for Monitor {
             Monitor::Tx(m) => write!(f, "<TX> {}", m),
             Monitor::Rx(m) => write!(f, "<RX> {}", m),
             Monitor::TxError => write!(f, "TX ERROR"),
-            Monitor::RxError => write!(f, "RX ERROR"),
+            Monitor::RxError(e) => write!(f, "RX ERROR: {}", e),
         }
     }
 }
@@ -43,7 +44,164 @@ impl defmt::Format for Monitor {
             Monitor::Tx(m) => defmt::write!(fmt, "<TX> {}", m),
             Monitor::Rx(m) => defmt::write!(fmt, "<RX> {}", m),
             Monitor::TxError => defmt::write!(fmt, "TX ERROR"),
-            Monitor::RxError => defmt::write!(fmt, "RX ERROR"),
+            Monitor::RxError(e) => defmt::write!(fmt, "RX ERROR: {}", e),
+        }
+    }
+}
+
+/// Borrowed Monitor - avoids cloning msg on the monitor path
+///
+/// Serialises identically to Monitor (for M = Msg or MsgRef) so the
+/// receiver can decode it as Monitor.
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+pub enum MonitorRef<'a, M = Msg> {
+    Tx(&'a M),
+    Rx(&'a M),
+    RxError(MsgError),
+    TxError,
+}
+
+impl<'a, M> MonitorRef<'a, M> {
+    pub fn new_tx(msg: &'a M) -> Self {
+        Self::Tx(msg)
+    }
+    pub fn new_rx(msg: &'a M) -> Self {
+        Self::Rx(msg)
+    }
+    pub fn new_rxerror(e: MsgError) -> Self {
+        Self::RxError(e)
+    }
+    pub fn new_txerror() -> Self {
+        Self::TxError
+    }
+}
+
+impl MonitorRef<'_, Msg> {
+    pub fn to_owned(&self) -> Monitor {
+        match self {
+            MonitorRef::Tx(m) => Monitor::Tx((*m).clone()),
+            MonitorRef::Rx(m) => Monitor::Rx((*m).clone()),
+            MonitorRef::RxError(e) => Monitor::RxError(*e),
+            MonitorRef::TxError => Monitor::TxError,
+        }
+    }
+}
+
+impl MonitorRef<'_, MsgRef<'_>> {
+    pub fn to_owned(&self) -> Result<Monitor, MsgError> {
+        Ok(match self {
+            MonitorRef::Tx(m) => Monitor::Tx(m.to_msg()?),
+            MonitorRef::Rx(m) => Monitor::Rx(m.to_msg()?),
+            MonitorRef::RxError(e) => Monitor::RxError(*e),
+            MonitorRef::TxError => Monitor::TxError,
+        })
+    }
+}
+
+impl<M: Display> Display for MonitorRef<'_, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MonitorRef::Tx(m) => write!(f, "<TX> {}", m),
+            MonitorRef::Rx(m) => write!(f, "<RX> {}", m),
+            MonitorRef::TxError => write!(f, "TX ERROR"),
+            MonitorRef::RxError(e) => write!(f, "RX ERROR: {}", e),
+        }
+    }
+}
+
+impl<M: defmt::Format> defmt::Format for MonitorRef<'_, M> {
+    fn format(&self, fmt: defmt::Formatter) {
+        match self {
+            MonitorRef::Tx(m) => defmt::write!(fmt, "<TX> {}", m),
+            MonitorRef::Rx(m) => defmt::write!(fmt, "<RX> {}", m),
+            MonitorRef::TxError => defmt::write!(fmt, "TX ERROR"),
+            MonitorRef::RxError(e) => defmt::write!(fmt, "RX ERROR: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_monitor_ref_serialise() {
+        let msg = Msg::Recv(RxData {
+            id: 1,
+            src_addr: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+            dst_addr: [0xff; 6],
+            data: heapless::Vec::from_slice(b"hello").unwrap(),
+            rssi: -60,
+            meta: None,
+        });
+        let owned = postcard::to_vec::<_, MAX_MSG_LEN>(&Monitor::new_rx(&msg)).unwrap();
+        let borrowed = postcard::to_vec::<_, MAX_MSG_LEN>(&MonitorRef::new_rx(&msg)).unwrap();
+        assert_eq!(owned, borrowed);
+
+        let msg_ref = MsgRef::from(&msg);
+        let borrowed = postcard::to_vec::<_, MAX_MSG_LEN>(&MonitorRef::new_rx(&msg_ref)).unwrap();
+        assert_eq!(owned, borrowed);
+        assert_eq!(
+            postcard::from_bytes::<Monitor>(&borrowed).unwrap(),
+            Monitor::new_rx(&msg)
+        );
+        assert_eq!(
+            MonitorRef::new_rx(&msg_ref).to_string(),
+            Monitor::new_rx(&msg).to_string()
+        );
+    }
+
+    #[test]
+    fn test_monitor_trailers() {
+        let msgs = [
+            Msg::Recv(RxData {
+                id: u32::MAX,
+                src_addr: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                dst_addr: [0xff; 6],
+                data: heapless::Vec::from_slice(b"hello").unwrap(),
+                rssi: -70,
+                meta: Some(RxMeta {
+                    channel: 6,
+                    noise_floor: -95,
+                    rate: 11,
+                    sig_mode: 1,
+                    timestamp: 3_000_000_000,
+                }),
+            }),
+            Msg::Send(TxData {
+                id: 2,
+                dst_addr: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                data: heapless::Vec::from_slice(b"later").unwrap(),
+                defer: false,
+                schedule: Some(Schedule::delay(500)),
+            }),
+            Msg::Broadcast(BroadcastData {
+                id: 3,
+                data: heapless::Vec::from_slice(b"beacon").unwrap(),
+                interval: Some(1000),
+                replace: Some(1),
+            }),
+            Msg::HubConfig(HubConfig {
+                id: 4,
+                channel: Some(6),
+                pmk: None,
+                wake_window: None,
+                rate: None,
+                rx_meta: Some(true),
+            }),
+            Msg::Ack(Ack::error(5, 4, AckError::PeerNotFound)),
+        ];
+        for msg in msgs {
+            for m in [Monitor::new_tx(&msg), Monitor::new_rx(&msg)] {
+                let encoded = postcard::to_vec::<_, { MAX_MSG_LEN + 8 }>(&m).unwrap();
+                assert_eq!(postcard::from_bytes::<Monitor>(&encoded).unwrap(), m);
+            }
+            let msg_ref = MsgRef::from(&msg);
+            assert_eq!(
+                postcard::to_vec::<_, { MAX_MSG_LEN + 8 }>(&MonitorRef::new_tx(&msg_ref)).unwrap(),
+                postcard::to_vec::<_, { MAX_MSG_LEN + 8 }>(&Monitor::new_tx(&msg)).unwrap()
+            );
         }
     }
 }
@@ -0,0 +1,217 @@
+use crate::types::{BroadcastInfo, BroadcastList, MAX_BROADCASTS, MIN_BROADCAST_INTERVAL};
+use crate::{AckError, BroadcastData};
+
+// Hub side periodic broadcast table
+//
+// Reference implementation of the BroadcastData/StopBroadcast semantics.
+// Timestamps (ms) are supplied by the caller:
+//
+// >>> Msg::Broadcast(b) => {
+// >>>     match table.apply(&b, now) {
+// >>>         Ok(()) => { espnow.send(BROADCAST, &b.data); ack_ok() }
+// >>>         Err(e) => ack_error(e),
+// >>>     }
+// >>> }
+// >>> Msg::StopBroadcast(s) => table.stop(s.broadcast_id)
+// >>> ...
+// >>> while let Some(b) = table.poll(now) { espnow.send(BROADCAST, &b.data) }
+
+#[derive(Clone, Debug)]
+struct Entry {
+    broadcast: BroadcastData,
+    interval: u32,
+    next: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BroadcastTable<const N: usize = MAX_BROADCASTS> {
+    entries: heapless::Vec<Entry, N>,
+}
+
+impl<const N: usize> BroadcastTable<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: heapless::Vec::new(),
+        }
+    }
+
+    fn position(&self, broadcast_id: u32) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.broadcast.id == broadcast_id)
+    }
+
+    /// Apply BroadcastData received at now
+    ///
+    /// The caller sends the broadcast immediately - periodic broadcasts are
+    /// then returned by poll every interval.
+    pub fn apply(&mut self, b: &BroadcastData, now: u64) -> Result<(), AckError> {
+        if matches!(b.interval, Some(i) if i < MIN_BROADCAST_INTERVAL) {
+            return Err(AckError::InvalidArg);
+        }
+        let replace = match b.replace {
+            Some(r) => Some(self.position(r).ok_or(AckError::BroadcastNotFound)?),
+            // Resending a running broadcast updates it
+            None => self.position(b.id),
+        };
+        match (b.interval, replace) {
+            (Some(interval), Some(i)) => {
+                self.entries[i] = Entry {
+                    broadcast: b.clone(),
+                    interval,
+                    next: now + interval as u64,
+                };
+            }
+            (Some(interval), None) => {
+                self.entries
+                    .push(Entry {
+                        broadcast: b.clone(),
+                        interval,
+                        next: now + interval as u64,
+                    })
+                    .map_err(|_| AckError::BroadcastLimit)?;
+            }
+            (None, Some(i)) => {
+                // Replaced by one-shot broadcast
+                self.entries.remove(i);
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+
+    pub fn stop(&mut self, broadcast_id: u32) -> Result<(), AckError> {
+        let i = self
+            .position(broadcast_id)
+            .ok_or(AckError::BroadcastNotFound)?;
+        self.entries.remove(i);
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Next broadcast due at now
+    pub fn poll(&mut self, now: u64) -> Option<&BroadcastData> {
+        let e = self.entries.iter_mut().find(|e| e.next <= now)?;
+        e.next += e.interval as u64;
+        if e.next <= now {
+            // Fell behind - skip missed intervals
+            e.next = now + e.interval as u64;
+        }
+        Some(&e.broadcast)
+    }
+
+    /// Time of next due broadcast
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.entries.iter().map(|e| e.next).min()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Reply to ListBroadcasts
+    pub fn list(&self, id: u32, rx_id: u32) -> BroadcastList {
+        BroadcastList {
+            id,
+            rx_id,
+            broadcasts: self
+                .entries
+                .iter()
+                .take(MAX_BROADCASTS)
+                .map(|e| BroadcastInfo {
+                    broadcast_id: e.broadcast.id,
+                    interval: e.interval,
+                    len: e.broadcast.data.len() as u16,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn b(id: u32, interval: Option<u32>, replace: Option<u32>) -> BroadcastData {
+        BroadcastData {
+            id,
+            data: heapless::Vec::from_slice(b"beacon").unwrap(),
+            interval,
+            replace,
+        }
+    }
+
+    #[test]
+    fn test_periodic() {
+        let mut t = BroadcastTable::<4>::new();
+        t.apply(&b(1, Some(100), None), 0).unwrap();
+        t.apply(&b(2, None, None), 0).unwrap();
+        assert_eq!(t.len(), 1);
+        assert_eq!(t.next_deadline(), Some(100));
+        assert!(t.poll(99).is_none());
+        assert_eq!(t.poll(100).map(|b| b.id), Some(1));
+        assert!(t.poll(100).is_none());
+        // Missed intervals are skipped
+        assert_eq!(t.poll(1000).map(|b| b.id), Some(1));
+        assert_eq!(t.next_deadline(), Some(1100));
+    }
+
+    #[test]
+    fn test_stop() {
+        let mut t = BroadcastTable::<4>::new();
+        t.apply(&b(1, Some(100), None), 0).unwrap();
+        assert_eq!(t.stop(2), Err(AckError::BroadcastNotFound));
+        assert_eq!(t.stop(1), Ok(()));
+        assert!(t.is_empty());
+        assert!(t.poll(100).is_none());
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut t = BroadcastTable::<4>::new();
+        t.apply(&b(1, Some(100), None), 0).unwrap();
+        assert_eq!(
+            t.apply(&b(2, Some(50), Some(9)), 0),
+            Err(AckError::BroadcastNotFound)
+        );
+        t.apply(&b(2, Some(50), Some(1)), 10).unwrap();
+        let list = t.list(3, 4);
+        assert_eq!(
+            list.broadcasts.as_slice(),
+            &[BroadcastInfo {
+                broadcast_id: 2,
+                interval: 50,
+                len: 6
+            }]
+        );
+        assert_eq!(t.next_deadline(), Some(60));
+        // Replace with one-shot stops the broadcast
+        t.apply(&b(3, None, Some(2)), 20).unwrap();
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut t = BroadcastTable::<2>::new();
+        assert_eq!(
+            t.apply(&b(1, Some(MIN_BROADCAST_INTERVAL - 1), None), 0),
+            Err(AckError::InvalidArg)
+        );
+        t.apply(&b(1, Some(100), None), 0).unwrap();
+        t.apply(&b(2, Some(100), None), 0).unwrap();
+        assert_eq!(
+            t.apply(&b(3, Some(100), None), 0),
+            Err(AckError::BroadcastLimit)
+        );
+        // Update of running broadcast is allowed when full
+        t.apply(&b(2, Some(200), None), 0).unwrap();
+        assert_eq!(t.len(), 2);
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{AckError, HubConfig, InitConfig, Msg, PeerInfo};
+
+pub use crate::types::MAX_PEERS;
+
+// Server side mirror of the hub peer table
+//
+// Enforces the ESP-NOW peer limits and channel consistency before a request
+// is sent, so the server does not have to wait for the hub to reject it.
+// Keep one registry per hub and apply requests once they are acknowledged:
+//
+// >>> Msg::Init(init) => registry.init(&init),
+// >>> ...
+// >>> registry.check(&msg)?;
+// >>> client.call(&msg).await?;
+// >>> registry.apply(&msg)?;
+
+/// Encrypted peer limit (ESP_NOW_MAX_ENCRYPT_PEER_NUM)
+pub const MAX_ENCRYPTED_PEERS: usize = 6;
+#[derive(Clone, Debug, Default)]
+pub struct PeerRegistry {
+    peers: heapless::Vec<PeerInfo, MAX_PEERS>,
+    /// Hub channel (None until known)
+    channel: Option<u8>,
+}
+
+impl PeerRegistry {
+    pub const fn new() -> Self {
+        Self {
+            peers: heapless::Vec::new(),
+            channel: None,
+        }
+    }
+
+    /// Hub (re)started - peer table is empty
+    pub fn init(&mut self, init: &InitConfig) {
+        self.peers.clear();
+        self.channel = Some(init.channel);
+    }
+
+    fn position(&self, address: &[u8; 6]) -> Option<usize> {
+        self.peers.iter().position(|p| &p.peer_address == address)
+    }
+
+    /// Check peer is valid and on the hub channel (ignoring table limits)
+    fn check_peer(&self, peer: &PeerInfo) -> Result<(), AckError> {
+        peer.validate_request()?;
+        match (peer.channel, self.channel) {
+            (Some(c), Some(hub)) if c != 0 && c != hub => Err(AckError::InvalidChannel),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_add(&self, peer: &PeerInfo) -> Result<(), AckError> {
+        self.check_peer(peer)?;
+        if self.position(&peer.peer_address).is_some() {
+            return Err(AckError::PeerExists);
+        }
+        if self.peers.is_full() || (peer.encrypt && self.encrypted() >= MAX_ENCRYPTED_PEERS) {
+            return Err(AckError::PeerListFull);
+        }
+        Ok(())
+    }
+
+    pub fn check_modify(&self, peer: &PeerInfo) -> Result<(), AckError> {
+        self.check_peer(peer)?;
+        let i = self
+            .position(&peer.peer_address)
+            .ok_or(AckError::PeerNotFound)?;
+        if peer.encrypt && !self.peers[i].encrypt && self.encrypted() >= MAX_ENCRYPTED_PEERS {
+            return Err(AckError::PeerListFull);
+        }
+        Ok(())
+    }
+
+    pub fn check_remove(&self, address: &[u8; 6]) -> Result<(), AckError> {
+        self.position(address)
+            .map(|_| ())
+            .ok_or(AckError::PeerNotFound)
+    }
+
+    /// Channel change must be consistent with peers pinned to a channel
+    pub fn check_config(&self, config: &HubConfig) -> Result<(), AckError> {
+        config.validate()?;
+        match config.channel {
+            Some(c)
+                if self
+                    .peers
+                    .iter()
+                    .any(|p| matches!(p.channel, Some(ch) if ch != 0 && ch != c)) =>
+            {
+                Err(AckError::InvalidChannel)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Validate request and check it against mirrored state
+    pub fn check(&self, msg: &Msg) -> Result<(), AckError> {
+        msg.validate()?;
+        match msg {
+            Msg::AddPeer(p) => self.check_add(p),
+            Msg::ModifyPeer(p) => self.check_modify(p),
+            Msg::RemovePeer(p) => self.check_remove(&p.address),
+            Msg::HubConfig(c) => self.check_config(c),
+            _ => Ok(()),
+        }
+    }
+
+    /// Apply acknowledged request
+    pub fn apply(&mut self, msg: &Msg) -> Result<(), AckError> {
+        self.check(msg)?;
+        match msg {
+            Msg::AddPeer(p) => {
+                // Capacity checked above
+                let _ = self.peers.push(p.clone());
+            }
+            Msg::ModifyPeer(p) => {
+                if let Some(i) = self.position(&p.peer_address) {
+                    self.peers[i] = p.clone();
+                }
+            }
+            Msg::RemovePeer(p) => {
+                if let Some(i) = self.position(&p.address) {
+                    self.peers.remove(i);
+                }
+            }
+            Msg::HubConfig(HubConfig {
+                channel: Some(c), ..
+            }) => self.channel = Some(*c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, address: &[u8; 6]) -> Option<&PeerInfo> {
+        self.position(address).map(|i| &self.peers[i])
+    }
+
+    pub fn peers(&self) -> &[PeerInfo] {
+        &self.peers
+    }
+
+    pub fn channel(&self) -> Option<u8> {
+        self.channel
+    }
+
+    pub fn encrypted(&self) -> usize {
+        self.peers.iter().filter(|p| p.encrypt).count()
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PeerAddress;
+
+    fn peer(n: u8, encrypt: bool, channel: Option<u8>) -> PeerInfo {
+        PeerInfo {
+            id: n as u32,
+            peer_address: [0x02, 0, 0, 0, 0, n],
+            lmk: encrypt.then_some([n; 16]),
+            channel,
+            encrypt,
+        }
+    }
+
+    fn config(channel: Option<u8>) -> HubConfig {
+        HubConfig {
+            id: 1,
+            channel,
+            pmk: None,
+            wake_window: None,
+            rate: None,
+            rx_meta: None,
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut r = PeerRegistry::new();
+        for n in 0..MAX_PEERS as u8 {
+            let encrypt = (n as usize) < MAX_ENCRYPTED_PEERS;
+            r.apply(&Msg::AddPeer(peer(n, encrypt, None))).unwrap();
+        }
+        assert_eq!((r.len(), r.encrypted()), (MAX_PEERS, MAX_ENCRYPTED_PEERS));
+        assert_eq!(
+            r.check(&Msg::AddPeer(peer(100, false, None))),
+            Err(AckError::PeerListFull)
+        );
+        assert_eq!(
+            r.check(&Msg::AddPeer(peer(0, false, None))),
+            Err(AckError::PeerExists)
+        );
+        // Encrypted limit applies to ModifyPeer
+        assert_eq!(
+            r.check(&Msg::ModifyPeer(peer(10, true, None))),
+            Err(AckError::PeerListFull)
+        );
+        r.apply(&Msg::ModifyPeer(peer(0, false, None))).unwrap();
+        r.apply(&Msg::ModifyPeer(peer(10, true, None))).unwrap();
+        assert!(r.get(&peer(10, true, None).peer_address).unwrap().encrypt);
+        let remove = Msg::RemovePeer(PeerAddress {
+            id: 2,
+            address: peer(19, false, None).peer_address,
+        });
+        r.apply(&remove).unwrap();
+        assert_eq!(r.apply(&remove), Err(AckError::PeerNotFound));
+        assert_eq!(
+            r.check(&Msg::AddPeer(peer(100, true, None))),
+            Err(AckError::PeerListFull)
+        );
+        r.apply(&Msg::AddPeer(peer(100, false, None))).unwrap();
+        assert_eq!(r.len(), MAX_PEERS);
+    }
+
+    #[test]
+    fn test_invalid_peer() {
+        let r = PeerRegistry::new();
+        let mut p = peer(1, true, None);
+        p.lmk = None;
+        assert_eq!(r.check_add(&p), Err(AckError::InvalidArg));
+        p.peer_address = [0xff; 6];
+        p.lmk = Some([0; 16]);
+        assert_eq!(r.check_add(&p), Err(AckError::InvalidArg));
+        assert_eq!(
+            r.check_add(&peer(1, false, Some(15))),
+            Err(AckError::InvalidChannel)
+        );
+        assert_eq!(
+            r.check_modify(&peer(1, false, None)),
+            Err(AckError::PeerNotFound)
+        );
+    }
+
+    #[test]
+    fn test_channel() {
+        let mut r = PeerRegistry::new();
+        r.init(&InitConfig {
+            id: 0,
+            address: [0x01; 6],
+            channel: 6,
+            api_version: 1,
+            now_version: 1,
+        });
+        r.apply(&Msg::AddPeer(peer(1, false, Some(6)))).unwrap();
+        r.apply(&Msg::AddPeer(peer(2, false, Some(0)))).unwrap();
+        assert_eq!(
+            r.check(&Msg::AddPeer(peer(3, false, Some(1)))),
+            Err(AckError::InvalidChannel)
+        );
+        // Peer 1 is pinned to channel 6
+        assert_eq!(
+            r.check(&Msg::HubConfig(config(Some(11)))),
+            Err(AckError::InvalidChannel)
+        );
+        assert_eq!(
+            r.check(&Msg::HubConfig(config(Some(0)))),
+            Err(AckError::InvalidChannel)
+        );
+        r.apply(&Msg::HubConfig(config(None))).unwrap();
+        r.apply(&Msg::ModifyPeer(peer(1, false, None))).unwrap();
+        r.apply(&Msg::HubConfig(config(Some(11)))).unwrap();
+        assert_eq!(r.channel(), Some(11));
+        r.apply(&Msg::AddPeer(peer(3, false, Some(11)))).unwrap();
+        // Hub restart clears peers
+        r.init(&InitConfig {
+            id: 1,
+            address: [0x01; 6],
+            channel: 1,
+            api_version: 1,
+            now_version: 1,
+        });
+        assert!(r.is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{Ack, Msg};
+
+use core::time::Duration;
+
+// Retransmission of unacknowledged messages
+//
+// The server retransmits a message (with the same id) when its Ack has not
+// arrived within RetryPolicy::timeout(attempt). The hub keeps the Acks it has
+// sent in an AckCache so a retransmitted message is acknowledged again without
+// being applied twice:
+//
+// >>> if let Some(ack) = acks.check(&msg) { return Msg::Ack(ack.clone()) }
+// >>> let ack = handle(msg);
+// >>> acks.insert(ack.clone());
+// >>> Msg::Ack(ack)
+//
+// The server starts each connection with a new id epoch (see HubClient::new)
+// so Acks cached for an earlier connection never match a new request.
+
+/// Ack timeout and retry budget
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RetryPolicy {
+    /// Timeout for first transmission
+    pub timeout: Duration,
+    /// Timeout multiplier per retransmission
+    pub backoff: u32,
+    pub max_timeout: Duration,
+    /// Retransmissions before giving up
+    pub retries: u8,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_millis(250),
+            backoff: 2,
+            max_timeout: Duration::from_secs(2),
+            retries: 3,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Single transmission
+    pub const fn none(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            backoff: 1,
+            max_timeout: timeout,
+            retries: 0,
+        }
+    }
+
+    /// Ack timeout for attempt (0 = first transmission)
+    pub fn timeout(&self, attempt: u8) -> Duration {
+        let factor = self.backoff.saturating_pow(attempt as u32);
+        self.timeout
+            .checked_mul(factor)
+            .unwrap_or(self.max_timeout)
+            .min(self.max_timeout)
+    }
+
+    /// Worst case time to give up
+    pub fn total(&self) -> Duration {
+        (0..=self.retries).map(|a| self.timeout(a)).sum()
+    }
+}
+
+/// Recently sent Acks keyed by rx_id (oldest replaced when full)
+#[derive(Clone, Debug, Default)]
+pub struct AckCache<const N: usize = 16> {
+    acks: heapless::Vec<Ack, N>,
+    next: usize,
+}
+
+impl<const N: usize> AckCache<N> {
+    pub const fn new() -> Self {
+        Self {
+            acks: heapless::Vec::new(),
+            next: 0,
+        }
+    }
+
+    pub fn get(&self, rx_id: u32) -> Option<&Ack> {
+        self.acks.iter().find(|a| a.rx_id == rx_id)
+    }
+
+    /// Cached Ack if msg is a retransmission
+    pub fn check(&self, msg: &Msg) -> Option<&Ack> {
+        match msg {
+            Msg::Ack(_) => None,
+            msg => self.get(msg.get_id()),
+        }
+    }
+
+    pub fn insert(&mut self, ack: Ack) {
+        if let Some(a) = self.acks.iter_mut().find(|a| a.rx_id == ack.rx_id) {
+            *a = ack;
+        } else if let Err(ack) = self.acks.push(ack) {
+            self.acks[self.next] = ack;
+            self.next = (self.next + 1) % N;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.acks.clear();
+        self.next = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.acks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.acks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AckError, PeerAddress};
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_backoff() {
+        let p = RetryPolicy::default();
+        let t = (0..6).map(|a| p.timeout(a)).collect::<Vec<_>>();
+        assert_eq!(
+            t,
+            vec![ms(250), ms(500), ms(1000), ms(2000), ms(2000), ms(2000)]
+        );
+        assert_eq!(p.total(), ms(3750));
+        assert_eq!(p.timeout(u8::MAX), p.max_timeout);
+        let p = RetryPolicy::none(ms(100));
+        assert_eq!((p.timeout(0), p.total()), (ms(100), ms(100)));
+    }
+
+    #[test]
+    fn test_ack_cache() {
+        let mut c = AckCache::<2>::new();
+        let msg = |id| {
+            Msg::RemovePeer(PeerAddress {
+                id,
+                address: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+            })
+        };
+        assert!(c.check(&msg(1)).is_none());
+        c.insert(Ack::ok(100, 1));
+        c.insert(Ack::error(101, 2, AckError::PeerNotFound));
+        assert_eq!(c.check(&msg(1)), Some(&Ack::ok(100, 1)));
+        assert!(c.check(&Msg::Ack(Ack::ok(1, 0))).is_none());
+        // Oldest replaced
+        c.insert(Ack::ok(102, 3));
+        assert!(c.get(1).is_none());
+        assert_eq!(c.get(2), Some(&Ack::error(101, 2, AckError::PeerNotFound)));
+        c.insert(Ack::ok(103, 4));
+        assert!(c.get(2).is_none());
+        assert_eq!(c.len(), 2);
+        c.clear();
+        assert!(c.is_empty());
+    }
+}
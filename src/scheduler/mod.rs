@@ -0,0 +1,154 @@
+use crate::{AckError, TxData};
+
+// Hub side scheduled send queue
+//
+// Reference implementation of TxData schedule/CancelSend semantics.
+// Timestamps (ms, hub clock) are supplied by the caller so the scheduler can
+// be driven by a mock clock on the host:
+//
+// >>> Msg::Send(tx) if tx.schedule.is_some() => scheduler.schedule(tx, now)  // Ack
+// >>> Msg::CancelSend(c) => scheduler.cancel(c.tx_id)  // Ack
+// >>> ...
+// >>> while let Some(tx) = scheduler.poll(now) { send(tx) }  // TxStatus
+
+#[derive(Clone, Debug)]
+struct Entry {
+    tx: TxData,
+    due: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Scheduler<const N: usize = 8> {
+    entries: heapless::Vec<Entry, N>,
+}
+
+impl<const N: usize> Scheduler<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: heapless::Vec::new(),
+        }
+    }
+
+    /// Schedule TxData received at now (TxData without schedule is due now)
+    ///
+    /// Scheduling a TxData id that is already pending replaces it.
+    pub fn schedule(&mut self, tx: TxData, now: u64) -> Result<(), AckError> {
+        let due = tx.schedule.map_or(now, |s| s.due(now));
+        let entry = Entry { tx, due };
+        match self.entries.iter_mut().find(|e| e.tx.id == entry.tx.id) {
+            Some(e) => *e = entry,
+            None => self
+                .entries
+                .push(entry)
+                .map_err(|_| AckError::ScheduleFull)?,
+        }
+        Ok(())
+    }
+
+    pub fn cancel(&mut self, tx_id: u32) -> Result<(), AckError> {
+        let i = self
+            .entries
+            .iter()
+            .position(|e| e.tx.id == tx_id)
+            .ok_or(AckError::ScheduleNotFound)?;
+        self.entries.remove(i);
+        Ok(())
+    }
+
+    /// Next due TxData at now (earliest first, schedule cleared)
+    pub fn poll(&mut self, now: u64) -> Option<TxData> {
+        let (i, _) = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.due <= now)
+            .min_by_key(|(_, e)| e.due)?;
+        let mut tx = self.entries.remove(i).tx;
+        tx.schedule = None;
+        Some(tx)
+    }
+
+    /// Time of next due TxData
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.entries.iter().map(|e| e.due).min()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{drain, scheduled, tx, A};
+    use crate::Schedule;
+
+    /// Mock hub clock
+    struct Clock(u64);
+
+    impl Clock {
+        fn advance(&mut self, ms: u64) -> u64 {
+            self.0 += ms;
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut clock = Clock(1000);
+        let mut s = Scheduler::<4>::new();
+        s.schedule(scheduled(1, Schedule::delay(30_000)), clock.0)
+            .unwrap();
+        s.schedule(scheduled(2, Schedule::at(5000)), clock.0)
+            .unwrap();
+        s.schedule(scheduled(3, Schedule::delay(1000)), clock.0)
+            .unwrap();
+        assert_eq!(s.next_deadline(), Some(2000));
+        assert!(drain(|| s.poll(clock.advance(999))).is_empty());
+        assert_eq!(drain(|| s.poll(clock.advance(1))), vec![3]);
+        // Overdue messages are returned earliest first
+        let sent = core::iter::from_fn(|| s.poll(clock.advance(60_000))).collect::<Vec<_>>();
+        assert_eq!(sent.iter().map(|tx| tx.id).collect::<Vec<_>>(), vec![2, 1]);
+        assert!(sent.iter().all(|tx| tx.schedule.is_none()));
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_past_and_unscheduled() {
+        let mut s = Scheduler::<4>::new();
+        s.schedule(scheduled(1, Schedule::at(10)), 100).unwrap();
+        s.schedule(tx(2, A), 100).unwrap();
+        assert_eq!(drain(|| s.poll(100)), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut s = Scheduler::<4>::new();
+        s.schedule(scheduled(1, Schedule::delay(100)), 0).unwrap();
+        s.schedule(scheduled(2, Schedule::delay(100)), 0).unwrap();
+        assert_eq!(s.cancel(1), Ok(()));
+        assert_eq!(s.cancel(1), Err(AckError::ScheduleNotFound));
+        assert_eq!(drain(|| s.poll(100)), vec![2]);
+        assert_eq!(s.cancel(2), Err(AckError::ScheduleNotFound));
+    }
+
+    #[test]
+    fn test_full_and_replace() {
+        let mut s = Scheduler::<2>::new();
+        s.schedule(scheduled(1, Schedule::delay(100)), 0).unwrap();
+        s.schedule(scheduled(2, Schedule::delay(100)), 0).unwrap();
+        assert_eq!(
+            s.schedule(scheduled(3, Schedule::delay(100)), 0),
+            Err(AckError::ScheduleFull)
+        );
+        // Same id replaces pending send
+        s.schedule(scheduled(2, Schedule::delay(50)), 0).unwrap();
+        assert_eq!(s.len(), 2);
+        assert_eq!(s.next_deadline(), Some(50));
+    }
+}
@@ -0,0 +1,28 @@
+use crate::{Schedule, TxData};
+
+// Fixtures shared by the module tests
+
+pub const A: [u8; 6] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+pub const B: [u8; 6] = [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6];
+
+pub fn tx(id: u32, dst_addr: [u8; 6]) -> TxData {
+    TxData {
+        id,
+        dst_addr,
+        data: heapless::Vec::from_slice(b"hello").unwrap(),
+        defer: false,
+        schedule: None,
+    }
+}
+
+pub fn scheduled(id: u32, schedule: Schedule) -> TxData {
+    TxData {
+        schedule: Some(schedule),
+        ..tx(id, A)
+    }
+}
+
+/// Ids of the messages returned by poll until it returns None
+pub fn drain(poll: impl FnMut() -> Option<TxData>) -> Vec<u32> {
+    core::iter::from_fn(poll).map(|tx| tx.id).collect()
+}
@@ -1,3 +1,5 @@
+pub(crate) mod fixtures;
+
 #[cfg(all(test, feature = "js"))]
 mod js_test {
 
@@ -70,6 +72,45 @@ mod js_test {
                     dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
                     data: heapless::Vec::from_slice("HELLO".as_bytes())?,
                     rssi: -5,
+                    meta: None,
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recv_meta() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.Recv(
+                        new espnow.RxData(
+                            1234,
+                            "01:02:03:04:05:06".parse_mac(),
+                            "f1:f2:f3:f4:f5:f6".parse_mac(),
+                            "HELLO".to_buffer(),
+                            -5,
+                            new espnow.RxMeta(6, -95, 11, 1, 123456)
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::Recv(RxData {
+                    id: 1234,
+                    src_addr: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                    dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+                    data: heapless::Vec::from_slice("HELLO".as_bytes())?,
+                    rssi: -5,
+                    meta: Some(RxMeta {
+                        channel: 6,
+                        noise_floor: -95,
+                        rate: 11,
+                        sig_mode: 1,
+                        timestamp: 123456,
+                    }),
                 })
             )
             .await?,
@@ -97,7 +138,8 @@ mod js_test {
                     id: 1234,
                     dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
                     data: heapless::Vec::from_slice("HELLO".as_bytes())?,
-                    defer: false
+                    defer: false,
+                    schedule: None,
                 })
             )
             .await?,
@@ -123,7 +165,309 @@ mod js_test {
                 Msg::Broadcast(BroadcastData {
                     id: 1234,
                     data: heapless::Vec::from_slice("HELLO".as_bytes())?,
-                    interval: Some(10)
+                    interval: Some(10),
+                    replace: None,
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_replace() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.Broadcast(
+                        new espnow.BroadcastData(
+                            1235,
+                            "HELLO".to_buffer(),
+                            100,
+                            1234
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::Broadcast(BroadcastData {
+                    id: 1235,
+                    data: heapless::Vec::from_slice("HELLO".as_bytes())?,
+                    interval: Some(100),
+                    replace: Some(1234),
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stop_broadcast() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.StopBroadcast(new espnow.StopBroadcast(1235, 1234));
+                    m
+                "#
+                .into(),
+                Msg::StopBroadcast(StopBroadcast {
+                    id: 1235,
+                    broadcast_id: 1234
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_list() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.BroadcastList(
+                        new espnow.BroadcastList(
+                            1235,
+                            1234,
+                            [new espnow.BroadcastInfo(99, 1000, 5)]
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::BroadcastList(BroadcastList {
+                    id: 1235,
+                    rx_id: 1234,
+                    broadcasts: heapless::Vec::from_slice(&[BroadcastInfo {
+                        broadcast_id: 99,
+                        interval: 1000,
+                        len: 5
+                    }])
+                    .unwrap()
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deferred() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.FlushDeferred(new espnow.FlushDeferred(1234));
+                    m
+                "#
+                .into(),
+                Msg::FlushDeferred(FlushDeferred {
+                    id: 1234,
+                    dst_addr: None
+                })
+            )
+            .await?,
+            true
+        );
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.DiscardDeferred(
+                        new espnow.DiscardDeferred(1234, "f1:f2:f3:f4:f5:f6".parse_mac())
+                    );
+                    m
+                "#
+                .into(),
+                Msg::DiscardDeferred(DiscardDeferred {
+                    id: 1234,
+                    dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6]
+                })
+            )
+            .await?,
+            true
+        );
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.DeferredStatus(
+                        new espnow.DeferredStatus(
+                            1235,
+                            1234,
+                            [new espnow.DeferredDepth("f1:f2:f3:f4:f5:f6".parse_mac(), 3, 5000)]
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::DeferredStatus(DeferredStatus {
+                    id: 1235,
+                    rx_id: 1234,
+                    queues: heapless::Vec::from_slice(&[DeferredDepth {
+                        dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+                        depth: 3,
+                        age: 5000
+                    }])
+                    .unwrap()
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_send() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.Send(
+                        new espnow.TxData(
+                            1234,
+                            "f1:f2:f3:f4:f5:f6".parse_mac(),
+                            "HELLO".to_buffer(),
+                            false,
+                            espnow.Schedule.delay(30000)
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::Send(TxData {
+                    id: 1234,
+                    dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+                    data: heapless::Vec::from_slice("HELLO".as_bytes())?,
+                    defer: false,
+                    schedule: Some(Schedule::delay(30000)),
+                })
+            )
+            .await?,
+            true
+        );
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.CancelSend(new espnow.CancelSend(1235, 1234));
+                    m
+                "#
+                .into(),
+                Msg::CancelSend(CancelSend {
+                    id: 1235,
+                    tx_id: 1234
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_group() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.AddToGroup(
+                        new espnow.GroupMember(1234, 2, "f1:f2:f3:f4:f5:f6".parse_mac())
+                    );
+                    m
+                "#
+                .into(),
+                Msg::AddToGroup(GroupMember {
+                    id: 1234,
+                    group: 2,
+                    address: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6]
+                })
+            )
+            .await?,
+            true
+        );
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.SendGroup(
+                        new espnow.SendGroup(1235, 2, "HELLO".to_buffer(), false)
+                    );
+                    m
+                "#
+                .into(),
+                Msg::SendGroup(SendGroup {
+                    id: 1235,
+                    group: 2,
+                    data: heapless::Vec::from_slice("HELLO".as_bytes())?,
+                    defer: false
+                })
+            )
+            .await?,
+            true
+        );
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.GroupStatus(
+                        new espnow.GroupStatus(
+                            1236,
+                            1235,
+                            2,
+                            [new espnow.MemberResult("f1:f2:f3:f4:f5:f6".parse_mac(), true)]
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::GroupStatus(GroupStatus {
+                    id: 1236,
+                    rx_id: 1235,
+                    group: 2,
+                    results: heapless::Vec::from_slice(&[MemberResult {
+                        address: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+                        success: true
+                    }])
+                    .unwrap()
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rx_filter() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.SetRxFilter(
+                        new espnow.RxFilter(
+                            1234,
+                            false,
+                            [
+                                new espnow.RxFilterRule(false, "f1:f2:f3:f4:f5:f6".parse_mac()),
+                                new espnow.RxFilterRule(true, undefined, false, -70, "T:".to_buffer())
+                            ]
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::SetRxFilter(RxFilter {
+                    id: 1234,
+                    default_allow: false,
+                    rules: heapless::Vec::from_slice(&[
+                        RxFilterRule {
+                            allow: false,
+                            src_addr: Some([0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6]),
+                            broadcast: None,
+                            min_rssi: None,
+                            prefix: heapless::Vec::new(),
+                        },
+                        RxFilterRule {
+                            allow: true,
+                            src_addr: None,
+                            broadcast: Some(false),
+                            min_rssi: Some(-70),
+                            prefix: heapless::Vec::from_slice(b"T:")?,
+                        }
+                    ])
+                    .unwrap()
                 })
             )
             .await?,
@@ -132,6 +476,37 @@ mod js_test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_validation() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const errors = [];
+                    const check = (f) => { try { f() } catch (e) { errors.push(e.message) } };
+                    const mac = "f1:f2:f3:f4:f5:f6".parse_mac();
+                    // Redacted PeerList entry is valid, AddPeer requires the lmk
+                    const redacted = new espnow.PeerInfo(1234, mac, undefined, 5, true);
+                    check(() => espnow.Msg.AddPeer(redacted));
+                    check(() => new espnow.HubConfig(1234, 200));
+                    check(() => new espnow.BroadcastData(1234, "HELLO".to_buffer(), 0));
+                    check(() => new espnow.TxData(1234, mac, new ArrayBuffer(0), false));
+                    check(() => new espnow.TxData(1234, mac, "HELLO".to_buffer(), false));
+                    new espnow.Ack(1, 2, errors.join("|") == [
+                        "encrypt requires lmk",
+                        "invalid channel 200 (1-14)",
+                        "interval 0ms below minimum 10ms",
+                        "data is empty"
+                    ].join("|"))
+                "#
+                .into(),
+                Ack::ok(1, 2)
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_ack() -> anyhow::Result<()> {
         assert_eq!(
@@ -149,7 +524,182 @@ mod js_test {
                 Msg::Ack(Ack {
                     id: 1234,
                     rx_id: 9876,
-                    status: true
+                    status: true,
+                    error: None
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.Unknown(
+                        new espnow.UnknownMsg(
+                            1234,
+                            99,
+                            new Uint8Array([0xd2, 0x09, 0x00, 0x00]).buffer
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::Unknown(UnknownMsg {
+                    id: 1234,
+                    kind: 99,
+                    bytes: heapless::Vec::from_slice(&[0xd2, 0x09, 0x00, 0x00])?,
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ack_error() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.Ack(
+                        new espnow.Ack(
+                            1234,
+                            9876,
+                            false,
+                            "PeerListFull"
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::Ack(Ack::error(1234, 9876, AckError::PeerListFull))
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ping_pong() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.Ping(new espnow.Ping(1234, 1700000000000));
+                    m
+                "#
+                .into(),
+                Msg::Ping(Ping {
+                    id: 1234,
+                    timestamp: 1700000000000
+                })
+            )
+            .await?,
+            true
+        );
+        assert_eq!(
+            check_class(
+                r#"
+                    const ping = new espnow.Ping(1234, 1000);
+                    const m = espnow.Msg.Pong(ping.pong(1235, 2000));
+                    m
+                "#
+                .into(),
+                Msg::Pong(Pong {
+                    id: 1235,
+                    ping_id: 1234,
+                    ping_timestamp: 1000,
+                    timestamp: 2000
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_stats() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.GetStats(new espnow.GetStats(1234, 60000));
+                    m
+                "#
+                .into(),
+                Msg::GetStats(GetStats {
+                    id: 1234,
+                    interval: Some(60000)
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_peers() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.ListPeers(new espnow.ListPeers(1234, 10));
+                    m
+                "#
+                .into(),
+                Msg::ListPeers(ListPeers {
+                    id: 1234,
+                    offset: 10
+                })
+            )
+            .await?,
+            true
+        );
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.GetPeer(
+                        new espnow.PeerAddress(1234, "f1:f2:f3:f4:f5:f6".parse_mac())
+                    );
+                    m
+                "#
+                .into(),
+                Msg::GetPeer(PeerAddress {
+                    id: 1234,
+                    address: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+                })
+            )
+            .await?,
+            true
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tx_status() -> anyhow::Result<()> {
+        assert_eq!(
+            check_class(
+                r#"
+                    const m = espnow.Msg.TxStatus(
+                        new espnow.TxStatus(
+                            1235,
+                            1234,
+                            "f1:f2:f3:f4:f5:f6".parse_mac(),
+                            false,
+                            2
+                    ));
+                    m
+                "#
+                .into(),
+                Msg::TxStatus(TxStatus {
+                    id: 1235,
+                    tx_id: 1234,
+                    dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+                    success: false,
+                    retries: 2,
                 })
             )
             .await?,
@@ -294,7 +844,8 @@ mod js_test {
                     channel: Some(1),
                     pmk: Some([0; 16]),
                     wake_window: Some(2),
-                    rate: Some(rate::WifiPhyRate::RateMcs0Sgi)
+                    rate: Some(rate::WifiPhyRate::RateMcs0Sgi),
+                    rx_meta: None,
                 })
             )
             .await?,
@@ -303,3 +854,521 @@ mod js_test {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod msg_test {
+
+    use crate::deferred::DeferredQueue;
+    use crate::group::GroupSend;
+    use crate::periodic::BroadcastTable;
+    use crate::*;
+
+    fn roundtrip(m: Msg) {
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let encoded = m.to_slice(&mut buf).unwrap();
+        assert_eq!(Msg::from_slice(encoded).unwrap(), m);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        roundtrip(Msg::Send(TxData {
+            id: 1234,
+            dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+            data: heapless::Vec::from_slice(&[0xaa; MAX_DATA_LEN]).unwrap(),
+            defer: true,
+            schedule: None,
+        }));
+        roundtrip(Msg::Ack(Ack {
+            id: u32::MAX,
+            rx_id: 9876,
+            status: true,
+            error: None,
+        }));
+        roundtrip(Msg::Ack(Ack::error(1, 2, AckError::PeerListFull)));
+        roundtrip(Msg::Unknown(UnknownMsg {
+            id: 1234,
+            kind: 200,
+            bytes: heapless::Vec::from_slice(&[0xd2, 0x09, 0x01, 0x02]).unwrap(),
+        }));
+        let ping = Ping {
+            id: 1,
+            timestamp: u64::MAX,
+        };
+        roundtrip(Msg::Pong(ping.pong(2, 1234)));
+        roundtrip(Msg::Ping(ping));
+        roundtrip(Msg::GetStats(GetStats {
+            id: 1,
+            interval: None,
+        }));
+        let mut counters = HubCounters::new();
+        counters.tx(true);
+        counters.rx_queue(8);
+        roundtrip(Msg::HubStats(counters.report(2, u32::MAX, 1024)));
+        let peer = PeerInfo {
+            id: 3,
+            peer_address: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+            lmk: Some([0xaa; 16]),
+            channel: Some(6),
+            encrypt: true,
+        };
+        roundtrip(Msg::PeerList(PeerList::page(
+            4,
+            5,
+            0,
+            &[peer.clone(), peer],
+        )));
+        roundtrip(Msg::ListPeers(ListPeers { id: 6, offset: 10 }));
+        let tx = TxData {
+            id: 8,
+            dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+            data: heapless::Vec::new(),
+            defer: false,
+            schedule: None,
+        };
+        roundtrip(Msg::TxStatus(TxStatus::from_tx(9, &tx, true, 3)));
+        roundtrip(Msg::GetPeer(PeerAddress {
+            id: 7,
+            address: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+        }));
+        roundtrip(Msg::StopBroadcast(StopBroadcast {
+            id: 10,
+            broadcast_id: 3,
+        }));
+        roundtrip(Msg::ListBroadcasts(ListBroadcasts { id: 11 }));
+        let mut table = BroadcastTable::<MAX_BROADCASTS>::new();
+        for id in 0..MAX_BROADCASTS as u32 {
+            table.apply(&beacon(id, None), 0).unwrap();
+        }
+        roundtrip(Msg::BroadcastList(table.list(12, 11)));
+        roundtrip(Msg::FlushDeferred(FlushDeferred {
+            id: 13,
+            dst_addr: Some([0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6]),
+        }));
+        roundtrip(Msg::DiscardDeferred(DiscardDeferred {
+            id: 14,
+            dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+        }));
+        roundtrip(Msg::GetDeferred(GetDeferred { id: 15 }));
+        let mut queue = DeferredQueue::<4>::new(0);
+        queue.push(tx.clone(), 0).unwrap();
+        roundtrip(Msg::DeferredStatus(queue.status(16, 15, 100)));
+        roundtrip(Msg::Send(TxData {
+            schedule: Some(Schedule::at(u64::MAX)),
+            ..tx.clone()
+        }));
+        roundtrip(Msg::CancelSend(CancelSend { id: 17, tx_id: 8 }));
+        let member = GroupMember {
+            id: 18,
+            group: 2,
+            address: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+        };
+        roundtrip(Msg::AddToGroup(member.clone()));
+        roundtrip(Msg::RemoveFromGroup(member));
+        let send = SendGroup {
+            id: 19,
+            group: 2,
+            data: heapless::Vec::from_slice(b"lights off").unwrap(),
+            defer: true,
+        };
+        let mut status = GroupSend::new(20, &send, 1);
+        send.fan_out(&[[0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6]], || 21)
+            .for_each(|tx| status.sent(&tx));
+        roundtrip(Msg::GroupStatus(status.result(21, true).unwrap()));
+        roundtrip(Msg::SendGroup(send));
+        roundtrip(Msg::SetRxFilter(RxFilter::allow_all(22)));
+        roundtrip(Msg::SetRxFilter(RxFilter {
+            id: 23,
+            default_allow: false,
+            rules: heapless::Vec::from_slice(&[RxFilterRule {
+                allow: true,
+                src_addr: Some([0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6]),
+                broadcast: Some(true),
+                min_rssi: Some(-80),
+                prefix: heapless::Vec::from_slice(b"T:").unwrap(),
+            }])
+            .unwrap(),
+        }));
+    }
+
+    fn beacon(id: u32, replace: Option<u32>) -> BroadcastData {
+        BroadcastData {
+            id,
+            data: heapless::Vec::from_slice(b"beacon").unwrap(),
+            interval: Some(1000),
+            replace,
+        }
+    }
+
+    #[test]
+    fn test_broadcast_replace() {
+        roundtrip(Msg::Broadcast(beacon(2, Some(1))));
+        // Without replace the encoding is unchanged
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let plain = Msg::Broadcast(beacon(2, None))
+            .to_slice(&mut buf)
+            .unwrap()
+            .to_vec();
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let with_replace = Msg::Broadcast(beacon(2, Some(1)))
+            .to_slice(&mut buf)
+            .unwrap()
+            .to_vec();
+        let old: BroadcastData = postcard::from_bytes(&with_replace[2..]).unwrap();
+        assert_eq!(old, beacon(2, None));
+        assert_eq!(&with_replace[2..plain.len()], &plain[2..]);
+        let r = MsgRef::from_slice(&with_replace).unwrap();
+        assert!(matches!(
+            r,
+            MsgRef::Broadcast(BroadcastDataRef {
+                replace: Some(1),
+                ..
+            })
+        ));
+    }
+
+    fn rx(meta: Option<RxMeta>) -> Msg {
+        Msg::Recv(RxData {
+            id: 1234,
+            src_addr: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+            dst_addr: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+            data: heapless::Vec::from_slice(b"HELLO").unwrap(),
+            rssi: -50,
+            meta,
+        })
+    }
+
+    const RX_META: RxMeta = RxMeta {
+        channel: 6,
+        noise_floor: -95,
+        rate: 11,
+        sig_mode: 1,
+        timestamp: u32::MAX,
+    };
+
+    #[test]
+    fn test_rx_meta() {
+        roundtrip(rx(None));
+        roundtrip(rx(Some(RX_META)));
+        roundtrip(Msg::HubConfig(HubConfig {
+            id: 1,
+            channel: None,
+            pmk: None,
+            wake_window: None,
+            rate: None,
+            rx_meta: Some(true),
+        }));
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let encoded = rx(Some(RX_META)).to_slice(&mut buf).unwrap();
+        let r = MsgRef::from_slice(encoded).unwrap();
+        assert!(matches!(
+            r,
+            MsgRef::Recv(RxDataRef {
+                meta: Some(RX_META),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_rx_meta_compatible() {
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let plain = rx(None).to_slice(&mut buf).unwrap().to_vec();
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let with_meta = rx(Some(RX_META)).to_slice(&mut buf).unwrap().to_vec();
+        // Older decoder reads the body and ignores the trailer
+        let body = &with_meta[2..];
+        let old: RxData = postcard::from_bytes(body).unwrap();
+        assert_eq!(Msg::Recv(old), rx(None));
+        // Without meta the encoding is unchanged
+        assert_eq!(&with_meta[2..plain.len()], &plain[2..]);
+    }
+
+    #[test]
+    fn test_envelope() {
+        let m = Msg::RemovePeer(PeerAddress {
+            id: 1234,
+            address: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+        });
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let encoded = m.to_slice(&mut buf).unwrap();
+        assert_eq!(
+            encoded,
+            [7, 8, 0xd2, 0x09, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6]
+        );
+    }
+
+    #[test]
+    fn test_unknown_kind() {
+        // Body of future message kind - id followed by unknown fields
+        let encoded = [99, 5, 0xd2, 0x09, 0x01, 0x02, 0x03];
+        let m = Msg::from_slice(&encoded).unwrap();
+        assert_eq!(
+            m,
+            Msg::Unknown(UnknownMsg {
+                id: 1234,
+                kind: 99,
+                bytes: heapless::Vec::from_slice(&encoded[2..]).unwrap(),
+            })
+        );
+        assert_eq!(m.get_id(), 1234);
+        assert_eq!(m.kind(), 99);
+        let Msg::Unknown(u) = m else { unreachable!() };
+        assert_eq!(u.nack(1), Ack::error(1, 1234, AckError::Unsupported));
+    }
+
+    #[test]
+    fn test_trailing_fields_ignored() {
+        // Newer version appended a field to RemovePeer body
+        let encoded = [7, 9, 0x01, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xff, 0xff];
+        assert_eq!(
+            Msg::from_slice(&encoded).unwrap(),
+            Msg::RemovePeer(PeerAddress {
+                id: 1,
+                address: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+            })
+        );
+    }
+
+    #[test]
+    fn test_ack_error() {
+        for e in [
+            AckError::PeerNotFound,
+            AckError::InvalidChannel,
+            AckError::Unsupported,
+        ] {
+            assert_eq!(e.to_string().parse::<AckError>(), Ok(e));
+        }
+        assert!("Bogus".parse::<AckError>().is_err());
+        assert_eq!(Ack::ok(1, 2).to_string(), "[1] Ack: rx_id=2 status=true");
+        assert_eq!(
+            Ack::error(1, 2, AckError::PeerNotFound).to_string(),
+            format!(
+                "[1] Ack: rx_id=2 status=false error={}",
+                AckError::PeerNotFound
+            )
+        );
+        assert_eq!(AckError::from(MsgError::ChecksumError), AckError::Decode);
+        assert_eq!(
+            "Unknown(200)".parse::<AckError>(),
+            Ok(AckError::Unknown(200))
+        );
+        assert_eq!(
+            AckError::from_code(AckError::GroupNotFound.code()),
+            AckError::GroupNotFound
+        );
+    }
+
+    #[test]
+    fn test_ack_error_compatible() {
+        // Ack without error is unchanged from the original encoding
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        assert_eq!(
+            Msg::Ack(Ack::ok(1, 2)).to_slice(&mut buf).unwrap(),
+            &[8, 3, 0x01, 0x02, 0x01]
+        );
+        // Error is a trailer which older decoders ignore
+        let encoded = Msg::Ack(Ack::error(1, 2, AckError::PeerNotFound))
+            .to_slice(&mut buf)
+            .unwrap()
+            .to_vec();
+        assert_eq!(encoded, &[8, 4, 0x01, 0x02, 0x00, 4]);
+        let old: Ack = postcard::from_bytes(&encoded[2..]).unwrap();
+        assert_eq!(
+            old,
+            Ack {
+                error: None,
+                ..Ack::error(1, 2, AckError::PeerNotFound)
+            }
+        );
+        assert_eq!(old.validate(), Ok(()));
+        // Error code from a newer version
+        assert_eq!(
+            Msg::from_slice(&[8, 4, 0x01, 0x02, 0x00, 200]),
+            Ok(Msg::Ack(Ack::error(1, 2, AckError::Unknown(200))))
+        );
+    }
+
+    #[cfg(not(feature = "espnow-v2"))]
+    #[test]
+    fn test_v2_payload_rejected() {
+        // TxData with 300 byte payload from an espnow-v2 build
+        let mut body = vec![0x01, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xac, 0x02];
+        body.extend_from_slice(&[0xaa; 300]);
+        body.push(0x00);
+        let mut encoded = vec![2, 0xb6, 0x02];
+        encoded.extend_from_slice(&body);
+        assert_eq!(body.len(), 0x136);
+        assert_eq!(Msg::from_slice(&encoded), Err(MsgError::CapacityError));
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert_eq!(Msg::from_slice(&[]), Err(MsgError::PostcardError));
+        assert_eq!(Msg::from_slice(&[8, 5, 0x01]), Err(MsgError::PostcardError));
+    }
+
+    fn peer(encrypt: bool, lmk: Option<[u8; 16]>) -> PeerInfo {
+        PeerInfo {
+            id: 1,
+            peer_address: [0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6],
+            lmk,
+            channel: Some(1),
+            encrypt,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(peer(true, Some([0; 16])).validate(), Ok(()));
+        assert_eq!(peer(false, None).validate(), Ok(()));
+        assert_eq!(
+            peer(true, None).validate_request(),
+            Err(ValidationError::MissingLmk)
+        );
+        // Redacted table entry
+        assert_eq!(peer(true, None).validate(), Ok(()));
+        assert_eq!(
+            Msg::AddPeer(peer(true, None)).validate(),
+            Err(ValidationError::MissingLmk)
+        );
+        let broadcast = PeerInfo {
+            peer_address: [0xff; 6],
+            ..peer(true, Some([0; 16]))
+        };
+        assert_eq!(
+            broadcast.validate(),
+            Err(ValidationError::EncryptedBroadcast)
+        );
+        let config = HubConfig {
+            id: 1,
+            channel: Some(200),
+            pmk: None,
+            wake_window: None,
+            rate: None,
+            rx_meta: None,
+        };
+        assert_eq!(
+            Msg::HubConfig(config).validate(),
+            Err(ValidationError::InvalidChannel(200))
+        );
+        let b = BroadcastData {
+            interval: Some(0),
+            ..beacon(1, None)
+        };
+        assert_eq!(
+            Msg::Broadcast(b.clone()).validate(),
+            Err(ValidationError::InvalidInterval(0))
+        );
+        assert_eq!(
+            MsgRef::Broadcast((&b).into()).validate(),
+            Err(ValidationError::InvalidInterval(0))
+        );
+        let tx = TxData {
+            id: 1,
+            dst_addr: [0xff; 6],
+            data: heapless::Vec::new(),
+            defer: false,
+            schedule: None,
+        };
+        assert_eq!(tx.validate(), Err(ValidationError::EmptyData));
+        let ack = Ack {
+            error: Some(AckError::NoMem),
+            ..Ack::ok(1, 2)
+        };
+        assert_eq!(ack.validate(), Err(ValidationError::AckStatus));
+        assert_eq!(Ack::error(1, 2, AckError::NoMem).validate(), Ok(()));
+        let list = PeerList {
+            id: 1,
+            rx_id: 2,
+            offset: 1,
+            total: 1,
+            peers: heapless::Vec::from_slice(&[peer(true, None)]).unwrap(),
+        };
+        assert_eq!(list.validate(), Err(ValidationError::PeerListRange));
+        let list = PeerList { offset: 0, ..list };
+        assert_eq!(Msg::PeerList(list.clone()).validate(), Ok(()));
+        let bad = PeerInfo {
+            channel: Some(200),
+            ..peer(false, None)
+        };
+        assert_eq!(
+            Msg::PeerList(PeerList {
+                peers: heapless::Vec::from_slice(&[bad]).unwrap(),
+                ..list
+            })
+            .validate(),
+            Err(ValidationError::InvalidChannel(200))
+        );
+        let unknown = UnknownMsg {
+            id: 1234,
+            kind: 99,
+            bytes: heapless::Vec::from_slice(&[0xd2, 0x09, 0x00, 0x00]).unwrap(),
+        };
+        assert_eq!(Msg::Unknown(unknown.clone()).validate(), Ok(()));
+        assert_eq!(
+            UnknownMsg {
+                kind: 29,
+                ..unknown.clone()
+            }
+            .validate(),
+            Err(ValidationError::KnownKind(29))
+        );
+        assert_eq!(
+            UnknownMsg {
+                id: 1,
+                ..unknown.clone()
+            }
+            .validate(),
+            Err(ValidationError::UnknownId)
+        );
+        assert_eq!(
+            UnknownMsg {
+                bytes: heapless::Vec::new(),
+                ..unknown
+            }
+            .validate(),
+            Err(ValidationError::UnknownId)
+        );
+        assert_eq!(
+            AckError::from(ValidationError::InvalidChannel(0)),
+            AckError::InvalidChannel
+        );
+        assert_eq!(
+            AckError::from(ValidationError::MissingLmk),
+            AckError::InvalidArg
+        );
+    }
+
+    #[test]
+    fn test_from_slice_validated() {
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let m = Msg::AddPeer(peer(true, None));
+        let encoded = m.to_slice(&mut buf).unwrap();
+        let e = Msg::from_slice_validated(encoded).unwrap_err();
+        assert_eq!(
+            e,
+            InvalidMsg {
+                id: Some(1),
+                error: AckError::InvalidArg
+            }
+        );
+        assert_eq!(e.ack(10), Some(Ack::error(10, 1, AckError::InvalidArg)));
+        assert_eq!(MsgRef::from_slice_validated(encoded).unwrap_err(), e);
+        // Valid
+        let m = Msg::AddPeer(peer(true, Some([0; 16])));
+        let encoded = m.to_slice(&mut buf).unwrap();
+        assert_eq!(Msg::from_slice_validated(encoded), Ok(m.clone()));
+        assert_eq!(MsgRef::from_slice_validated(encoded), Ok((&m).into()));
+        // Id recovered from undecodable body
+        let e = Msg::from_slice_validated(&[8, 2, 0x07, 0x01]).unwrap_err();
+        assert_eq!(
+            e,
+            InvalidMsg {
+                id: Some(7),
+                error: AckError::Decode
+            }
+        );
+        let e = MsgRef::from_slice_validated(&[8, 5, 0x01]).unwrap_err();
+        assert_eq!((e.id, e.ack(10)), (None, None));
+    }
+}
@@ -0,0 +1,318 @@
+use crate::framing::{encode_frame_with, Checksum, FrameDecoder, MAX_FRAME_LEN};
+use crate::types::msg::MsgError;
+use crate::Msg;
+
+use core::fmt::Display;
+
+// embedded-io transport :: read/write framed Msg over a UART (or any byte
+// stream) using fixed size buffers. Each reader/writer supports both the
+// blocking (embedded_io) and async (embedded_io_async) traits.
+
+const READ_CHUNK: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransportError<E> {
+    Io(E),
+    Msg(MsgError),
+    Eof,
+}
+
+impl<E> From<MsgError> for TransportError<E> {
+    fn from(e: MsgError) -> Self {
+        TransportError::Msg(e)
+    }
+}
+
+impl<E: core::fmt::Debug> Display for TransportError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TransportError::Io(e) => write!(f, "IO error: {:?}", e),
+            TransportError::Msg(e) => write!(f, "Msg error: {}", e),
+            TransportError::Eof => write!(f, "EOF"),
+        }
+    }
+}
+
+/// Read framed Msg from R
+///
+/// Decode errors are returned for the offending frame only - the reader
+/// resynchronises and subsequent calls continue with the next frame.
+///
+/// `read_async` is cancel safe if the underlying read is: the buffered length
+/// is only updated once a read completes, so dropping the future mid-read
+/// loses no buffered data and decodes nothing stale.
+pub struct MsgReader<R, const N: usize = MAX_FRAME_LEN> {
+    reader: R,
+    decoder: FrameDecoder<N>,
+    buf: [u8; READ_CHUNK],
+    // Filled length of buf
+    len: usize,
+    pos: usize,
+}
+
+impl<R, const N: usize> MsgReader<R, N> {
+    pub fn new(reader: R) -> Self {
+        Self::with_checksum(reader, Checksum::None)
+    }
+
+    pub fn with_checksum(reader: R, checksum: Checksum) -> Self {
+        Self {
+            reader,
+            decoder: FrameDecoder::with_checksum(checksum),
+            buf: [0; READ_CHUNK],
+            len: 0,
+            pos: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Decode next frame from buffered data
+    fn next_buffered(&mut self) -> Option<Result<Msg, MsgError>> {
+        let (n, result) = self.decoder.push(&self.buf[self.pos..self.len]);
+        self.pos += n;
+        result
+    }
+
+    // Record completed read of n bytes into buf
+    fn filled(&mut self, n: usize) {
+        self.pos = 0;
+        self.len = n;
+    }
+}
+
+impl<R: embedded_io::Read, const N: usize> MsgReader<R, N> {
+    pub fn read(&mut self) -> Result<Msg, TransportError<R::Error>> {
+        loop {
+            if let Some(result) = self.next_buffered() {
+                return Ok(result?);
+            }
+            let n = self
+                .reader
+                .read(&mut self.buf)
+                .map_err(TransportError::Io)?;
+            self.filled(n);
+            if n == 0 {
+                return Err(TransportError::Eof);
+            }
+        }
+    }
+}
+
+impl<R: embedded_io_async::Read, const N: usize> MsgReader<R, N> {
+    pub async fn read_async(&mut self) -> Result<Msg, TransportError<R::Error>> {
+        loop {
+            if let Some(result) = self.next_buffered() {
+                return Ok(result?);
+            }
+            let n = self
+                .reader
+                .read(&mut self.buf)
+                .await
+                .map_err(TransportError::Io)?;
+            self.filled(n);
+            if n == 0 {
+                return Err(TransportError::Eof);
+            }
+        }
+    }
+}
+
+/// Write framed Msg to W
+pub struct MsgWriter<W> {
+    writer: W,
+    checksum: Checksum,
+}
+
+impl<W> MsgWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_checksum(writer, Checksum::None)
+    }
+
+    pub fn with_checksum(writer: W, checksum: Checksum) -> Self {
+        Self { writer, checksum }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: embedded_io::Write> MsgWriter<W> {
+    pub fn write(&mut self, msg: &Msg) -> Result<(), TransportError<W::Error>> {
+        let mut buf = [0_u8; MAX_FRAME_LEN];
+        let frame = encode_frame_with(msg, self.checksum, &mut buf)?;
+        self.writer.write_all(frame).map_err(TransportError::Io)
+    }
+
+    pub fn flush(&mut self) -> Result<(), TransportError<W::Error>> {
+        self.writer.flush().map_err(TransportError::Io)
+    }
+}
+
+impl<W: embedded_io_async::Write> MsgWriter<W> {
+    pub async fn write_async(&mut self, msg: &Msg) -> Result<(), TransportError<W::Error>> {
+        let mut buf = [0_u8; MAX_FRAME_LEN];
+        let frame = encode_frame_with(msg, self.checksum, &mut buf)?;
+        self.writer
+            .write_all(frame)
+            .await
+            .map_err(TransportError::Io)
+    }
+
+    pub async fn flush_async(&mut self) -> Result<(), TransportError<W::Error>> {
+        self.writer.flush().await.map_err(TransportError::Io)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    use core::future::Future;
+    use core::task::{Context, Poll, Waker};
+
+    // In-memory readers/writers never return Pending
+    fn block_on<F: Future>(f: F) -> F::Output {
+        let mut f = core::pin::pin!(f);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(v) = f.as_mut().poll(&mut cx) {
+                return v;
+            }
+        }
+    }
+
+    fn msgs() -> [Msg; 3] {
+        [
+            Msg::RemovePeer(PeerAddress {
+                id: 1,
+                address: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+            }),
+            Msg::Broadcast(BroadcastData {
+                id: 2,
+                data: heapless::Vec::from_slice(&[0x55; 200]).unwrap(),
+                interval: None,
+                replace: None,
+            }),
+            Msg::Ack(Ack::ok(3, 2)),
+        ]
+    }
+
+    #[test]
+    fn test_blocking_roundtrip() {
+        let mut buf = [0_u8; 3 * MAX_FRAME_LEN];
+        let mut writer = MsgWriter::with_checksum(&mut buf[..], Checksum::Crc16);
+        for m in msgs() {
+            writer.write(&m).unwrap();
+        }
+        let remaining = writer.into_inner().len();
+        let written = buf.len() - remaining;
+
+        let mut reader = MsgReader::<_>::with_checksum(&buf[..written], Checksum::Crc16);
+        for m in msgs() {
+            assert_eq!(reader.read(), Ok(m));
+        }
+        assert_eq!(reader.read(), Err(TransportError::Eof));
+    }
+
+    #[test]
+    fn test_async_roundtrip() {
+        let mut buf = [0_u8; 3 * MAX_FRAME_LEN];
+        let mut writer = MsgWriter::new(&mut buf[..]);
+        for m in msgs() {
+            block_on(writer.write_async(&m)).unwrap();
+        }
+        let remaining = writer.into_inner().len();
+        let written = buf.len() - remaining;
+
+        let mut reader = MsgReader::<_>::new(&buf[..written]);
+        for m in msgs() {
+            assert_eq!(block_on(reader.read_async()), Ok(m));
+        }
+        assert_eq!(block_on(reader.read_async()), Err(TransportError::Eof));
+    }
+
+    #[test]
+    fn test_reader_resync() {
+        let mut buf = [0_u8; 3 * MAX_FRAME_LEN];
+        let mut writer = MsgWriter::new(&mut buf[..]);
+        for m in msgs() {
+            writer.write(&m).unwrap();
+        }
+        let remaining = writer.into_inner().len();
+        let written = buf.len() - remaining;
+
+        // Drop the start of the stream
+        let mut reader = MsgReader::<_>::new(&buf[4..written]);
+        assert!(matches!(reader.read(), Err(TransportError::Msg(_))));
+        assert_eq!(reader.read(), Ok(msgs()[1].clone()));
+        assert_eq!(reader.read(), Ok(msgs()[2].clone()));
+    }
+
+    #[test]
+    fn test_writer_capacity() {
+        let mut buf = [0_u8; 16];
+        let mut writer = MsgWriter::new(&mut buf[..]);
+        assert!(writer.write(&msgs()[1]).is_err());
+    }
+
+    /// Returns Pending before every read (simulates a slow UART)
+    struct SlowReader<'a> {
+        data: &'a [u8],
+        ready: bool,
+    }
+
+    impl embedded_io::ErrorType for SlowReader<'_> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl embedded_io_async::Read for SlowReader<'_> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            core::future::poll_fn(|cx| {
+                self.ready = !self.ready;
+                match self.ready {
+                    true => {
+                        cx.waker().wake_by_ref();
+                        Poll::Pending
+                    }
+                    false => Poll::Ready(()),
+                }
+            })
+            .await;
+            let n = buf.len().min(self.data.len()).min(7);
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_async_cancel() {
+        let mut buf = [0_u8; 3 * MAX_FRAME_LEN];
+        let mut writer = MsgWriter::new(&mut buf[..]);
+        for m in msgs() {
+            writer.write(&m).unwrap();
+        }
+        let remaining = writer.into_inner().len();
+        let written = buf.len() - remaining;
+
+        let mut reader = MsgReader::<_>::new(SlowReader {
+            data: &buf[..written],
+            ready: false,
+        });
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut out = Vec::new();
+        while out.len() < 3 {
+            // Poll once then drop the future (eg. lost a select!)
+            let mut f = core::pin::pin!(reader.read_async());
+            if let Poll::Ready(r) = f.as_mut().poll(&mut cx) {
+                out.push(r);
+            }
+        }
+        assert_eq!(out, msgs().map(Ok));
+    }
+}
@@ -1,10 +1,18 @@
 #[cfg(feature = "js")]
-use rquickjs::{class::Trace, JsLifetime};
+use rquickjs::{class::Trace, Ctx, Exception, JsLifetime};
 
+use crate::types::msg::MsgError;
+use crate::types::validate::ValidationError;
+
+use core::fmt;
 use core::fmt::Display;
-use serde::{Deserialize, Serialize};
+use core::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 // Bidirectional :: Msg respoonse
+//
+// The error is an optional trailer (absent when status is true) so the body
+// is unchanged for decoders which predate AckError.
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
@@ -12,14 +20,81 @@ pub struct Ack {
     pub id: u32,
     pub rx_id: u32,
     pub status: bool,
+    #[serde(skip)]
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub error: Option<AckError>, // None if status is true (or not reported)
+}
+
+impl Ack {
+    pub fn ok(id: u32, rx_id: u32) -> Self {
+        Self {
+            id,
+            rx_id,
+            status: true,
+            error: None,
+        }
+    }
+    pub fn error(id: u32, rx_id: u32, error: AckError) -> Self {
+        Self {
+            id,
+            rx_id,
+            status: false,
+            error: Some(error),
+        }
+    }
+
+    /// A failed Ack from an older version has no error
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        match self.status && self.error.is_some() {
+            true => Err(ValidationError::AckStatus),
+            false => Ok(()),
+        }
+    }
 }
 
 #[cfg(feature = "js")]
 #[rquickjs::methods]
 impl Ack {
     #[qjs(constructor)]
-    pub fn new(id: u32, rx_id: u32, status: bool) -> rquickjs::Result<Self> {
-        Ok(Self { id, rx_id, status })
+    pub fn new(
+        ctx: Ctx<'_>,
+        id: u32,
+        rx_id: u32,
+        status: bool,
+        error: Option<String>,
+    ) -> rquickjs::Result<Self> {
+        let error: Option<AckError> = match error {
+            Some(s) => Some(
+                (s.as_str())
+                    .try_into()
+                    .map_err(|_| Exception::throw_message(&ctx, "Invalid AckError"))?,
+            ),
+            None => None,
+        };
+        let m = Self {
+            id,
+            rx_id,
+            status,
+            error,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "rx_id")]
+    pub fn get_rx_id(&self) -> u32 {
+        self.rx_id
+    }
+    #[qjs(get, rename = "status")]
+    pub fn get_status(&self) -> bool {
+        self.status
+    }
+    #[qjs(get, rename = "error")]
+    pub fn get_error(&self) -> Option<String> {
+        self.error.as_ref().map(|e| e.to_string())
     }
     pub fn debug(&self) -> String {
         format!("Ack: {:?}", self)
@@ -32,7 +107,11 @@ impl Display for Ack {
             f,
             "[{}] Ack: rx_id={} status={}",
             self.id, self.rx_id, self.status,
-        )
+        )?;
+        match &self.error {
+            Some(e) => write!(f, " error={}", e),
+            None => Ok(()),
+        }
     }
 }
 
@@ -44,6 +123,201 @@ impl defmt::Format for Ack {
             self.id,
             self.rx_id,
             self.status,
-        )
+        );
+        if let Some(e) = &self.error {
+            defmt::write!(fmt, " error={}", e);
+        }
+    }
+}
+
+/// Negative acknowledgement reason
+///
+/// Mirrors the ESP-NOW (ESP_ERR_ESPNOW_*) error codes plus protocol level
+/// failures. Encoded as a u8 code - codes added by newer versions decode as
+/// Unknown.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum AckError {
+    // ESP-NOW
+    NotInit,
+    InvalidArg,
+    NoMem,
+    PeerListFull,
+    PeerNotFound,
+    Internal,
+    PeerExists,
+    Interface,
+    InvalidChannel,
+    // Protocol
+    Decode,
+    Capacity,
+    Unsupported,
+    BroadcastLimit,
+    BroadcastNotFound,
+    DeferQueueFull,
+    ScheduleFull,
+    ScheduleNotFound,
+    GroupFull,
+    GroupNotFound,
+    /// Code not known to this version
+    Unknown(u8),
+}
+
+impl AckError {
+    pub fn code(&self) -> u8 {
+        match self {
+            AckError::NotInit => 0,
+            AckError::InvalidArg => 1,
+            AckError::NoMem => 2,
+            AckError::PeerListFull => 3,
+            AckError::PeerNotFound => 4,
+            AckError::Internal => 5,
+            AckError::PeerExists => 6,
+            AckError::Interface => 7,
+            AckError::InvalidChannel => 8,
+            AckError::Decode => 9,
+            AckError::Capacity => 10,
+            AckError::Unsupported => 11,
+            AckError::BroadcastLimit => 12,
+            AckError::BroadcastNotFound => 13,
+            AckError::DeferQueueFull => 14,
+            AckError::ScheduleFull => 15,
+            AckError::ScheduleNotFound => 16,
+            AckError::GroupFull => 17,
+            AckError::GroupNotFound => 18,
+            AckError::Unknown(code) => *code,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => AckError::NotInit,
+            1 => AckError::InvalidArg,
+            2 => AckError::NoMem,
+            3 => AckError::PeerListFull,
+            4 => AckError::PeerNotFound,
+            5 => AckError::Internal,
+            6 => AckError::PeerExists,
+            7 => AckError::Interface,
+            8 => AckError::InvalidChannel,
+            9 => AckError::Decode,
+            10 => AckError::Capacity,
+            11 => AckError::Unsupported,
+            12 => AckError::BroadcastLimit,
+            13 => AckError::BroadcastNotFound,
+            14 => AckError::DeferQueueFull,
+            15 => AckError::ScheduleFull,
+            16 => AckError::ScheduleNotFound,
+            17 => AckError::GroupFull,
+            18 => AckError::GroupNotFound,
+            code => AckError::Unknown(code),
+        }
+    }
+}
+
+impl Serialize for AckError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for AckError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u8::deserialize(deserializer).map(AckError::from_code)
+    }
+}
+
+impl From<MsgError> for AckError {
+    fn from(e: MsgError) -> Self {
+        match e {
+            MsgError::CapacityError => AckError::Capacity,
+            MsgError::PostcardError | MsgError::FramingError | MsgError::ChecksumError => {
+                AckError::Decode
+            }
+        }
+    }
+}
+
+impl fmt::Display for AckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            AckError::NotInit => "NotInit",
+            AckError::InvalidArg => "InvalidArg",
+            AckError::NoMem => "NoMem",
+            AckError::PeerListFull => "PeerListFull",
+            AckError::PeerNotFound => "PeerNotFound",
+            AckError::Internal => "Internal",
+            AckError::PeerExists => "PeerExists",
+            AckError::Interface => "Interface",
+            AckError::InvalidChannel => "InvalidChannel",
+            AckError::Decode => "Decode",
+            AckError::Capacity => "Capacity",
+            AckError::Unsupported => "Unsupported",
+            AckError::BroadcastLimit => "BroadcastLimit",
+            AckError::BroadcastNotFound => "BroadcastNotFound",
+            AckError::DeferQueueFull => "DeferQueueFull",
+            AckError::ScheduleFull => "ScheduleFull",
+            AckError::ScheduleNotFound => "ScheduleNotFound",
+            AckError::GroupFull => "GroupFull",
+            AckError::GroupNotFound => "GroupNotFound",
+            AckError::Unknown(code) => return write!(f, "Unknown({})", code),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAckErrorError;
+
+impl fmt::Display for ParseAckErrorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to parse AckError")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseAckErrorError {}
+
+#[cfg(not(feature = "std"))]
+impl core::error::Error for ParseAckErrorError {}
+
+impl TryFrom<&str> for AckError {
+    type Error = ParseAckErrorError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "NotInit" => Ok(AckError::NotInit),
+            "InvalidArg" => Ok(AckError::InvalidArg),
+            "NoMem" => Ok(AckError::NoMem),
+            "PeerListFull" => Ok(AckError::PeerListFull),
+            "PeerNotFound" => Ok(AckError::PeerNotFound),
+            "Internal" => Ok(AckError::Internal),
+            "PeerExists" => Ok(AckError::PeerExists),
+            "Interface" => Ok(AckError::Interface),
+            "InvalidChannel" => Ok(AckError::InvalidChannel),
+            "Decode" => Ok(AckError::Decode),
+            "Capacity" => Ok(AckError::Capacity),
+            "Unsupported" => Ok(AckError::Unsupported),
+            "BroadcastLimit" => Ok(AckError::BroadcastLimit),
+            "BroadcastNotFound" => Ok(AckError::BroadcastNotFound),
+            "DeferQueueFull" => Ok(AckError::DeferQueueFull),
+            "ScheduleFull" => Ok(AckError::ScheduleFull),
+            "ScheduleNotFound" => Ok(AckError::ScheduleNotFound),
+            "GroupFull" => Ok(AckError::GroupFull),
+            "GroupNotFound" => Ok(AckError::GroupNotFound),
+            _ => s
+                .strip_prefix("Unknown(")
+                .and_then(|s| s.strip_suffix(')'))
+                .and_then(|code| code.parse().ok())
+                .map(AckError::from_code)
+                .ok_or(ParseAckErrorError),
+        }
+    }
+}
+
+impl FromStr for AckError {
+    type Err = ParseAckErrorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AckError::try_from(s)
     }
 }
@@ -1,13 +1,29 @@
 #[cfg(feature = "js")]
 use rquickjs::{class::Trace, ArrayBuffer, Ctx, Exception, JsLifetime};
 
-use crate::util::display_vec;
+use crate::types::validate::{check_interval, ValidationError};
+use crate::types::BroadcastDataRef;
 use crate::MAX_DATA_LEN;
 
 use core::fmt::Display;
 use serde::{Deserialize, Serialize};
 
 // Server -> Hub :: Broadcast esp-now msg
+//
+// With interval set the hub repeats the broadcast every interval ms until
+// stopped (StopBroadcast) - the broadcast is identified by its id. Setting
+// replace updates a running broadcast: the broadcast with id replace is
+// stopped and this one takes its place atomically (Ack BroadcastNotFound if
+// it is not running). At most MAX_BROADCASTS periodic broadcasts can run
+// concurrently (Ack BroadcastLimit) and interval must be at least
+// MIN_BROADCAST_INTERVAL (Ack InvalidArg).
+//
+// replace is encoded as an optional trailer (see RxData meta).
+
+/// Maximum concurrent periodic broadcasts per hub
+pub const MAX_BROADCASTS: usize = 4;
+/// Minimum periodic broadcast interval (ms)
+pub const MIN_BROADCAST_INTERVAL: u32 = 10;
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
@@ -16,6 +32,14 @@ pub struct BroadcastData {
     #[cfg_attr(feature = "js", qjs(skip_trace))]
     pub data: heapless::Vec<u8, MAX_DATA_LEN>,
     pub interval: Option<u32>,
+    #[serde(skip)]
+    pub replace: Option<u32>,
+}
+
+impl BroadcastData {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        BroadcastDataRef::from(self).validate()
+    }
 }
 
 #[cfg(feature = "js")]
@@ -27,13 +51,17 @@ impl BroadcastData {
         id: u32,
         data: ArrayBuffer<'_>,
         interval: Option<u32>,
+        replace: Option<u32>,
     ) -> rquickjs::Result<Self> {
-        Ok(Self {
+        let m = Self {
             id,
             data: heapless::Vec::from_slice(data.as_bytes().unwrap_or(&[]))
                 .map_err(|_| Exception::throw_message(&ctx, "data invalid"))?,
             interval,
-        })
+            replace,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
     }
     pub fn debug(&self) -> String {
         format!("BroadcastData: {:?}", self)
@@ -41,25 +69,242 @@ impl BroadcastData {
 }
 
 impl Display for BroadcastData {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", BroadcastDataRef::from(self))
+    }
+}
+
+impl defmt::Format for BroadcastData {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{}", BroadcastDataRef::from(self))
+    }
+}
+
+// Server -> Hub :: Stop periodic broadcast
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct StopBroadcast {
+    pub id: u32,
+    pub broadcast_id: u32,
+}
+
+impl StopBroadcast {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl StopBroadcast {
+    #[qjs(constructor)]
+    pub fn new(id: u32, broadcast_id: u32) -> Self {
+        Self { id, broadcast_id }
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "broadcast_id")]
+    pub fn get_broadcast_id(&self) -> u32 {
+        self.broadcast_id
+    }
+    pub fn debug(&self) -> String {
+        format!("StopBroadcast: {:?}", self)
+    }
+}
+
+impl Display for StopBroadcast {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "[{}] BroadcastData: interval={:?} data=\"{}\"",
+            "[{}] StopBroadcast: broadcast_id={}",
+            self.id, self.broadcast_id
+        )
+    }
+}
+
+impl defmt::Format for StopBroadcast {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] StopBroadcast: broadcast_id={}",
             self.id,
-            self.interval,
-            display_vec::<64, MAX_DATA_LEN>(&self.data)
+            self.broadcast_id
         )
     }
 }
 
-impl defmt::Format for BroadcastData {
+// Server -> Hub :: List running periodic broadcasts (hub replies with BroadcastList)
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct ListBroadcasts {
+    pub id: u32,
+}
+
+impl ListBroadcasts {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl ListBroadcasts {
+    #[qjs(constructor)]
+    pub fn new(id: u32) -> Self {
+        Self { id }
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    pub fn debug(&self) -> String {
+        format!("ListBroadcasts: {:?}", self)
+    }
+}
+
+impl Display for ListBroadcasts {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[{}] ListBroadcasts", self.id)
+    }
+}
+
+impl defmt::Format for ListBroadcasts {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "[{}] ListBroadcasts", self.id)
+    }
+}
+
+/// Running periodic broadcast
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, defmt::Format)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct BroadcastInfo {
+    pub broadcast_id: u32,
+    pub interval: u32,
+    /// Payload length
+    pub len: u16,
+}
+
+impl BroadcastInfo {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        check_interval(Some(self.interval))
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl BroadcastInfo {
+    #[qjs(constructor)]
+    pub fn new(ctx: Ctx<'_>, broadcast_id: u32, interval: u32, len: u16) -> rquickjs::Result<Self> {
+        let m = Self {
+            broadcast_id,
+            interval,
+            len,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
+    }
+    #[qjs(get, rename = "broadcast_id")]
+    pub fn get_broadcast_id(&self) -> u32 {
+        self.broadcast_id
+    }
+    #[qjs(get, rename = "interval")]
+    pub fn get_interval(&self) -> u32 {
+        self.interval
+    }
+    #[qjs(get, rename = "len")]
+    pub fn get_len(&self) -> u16 {
+        self.len
+    }
+}
+
+impl Display for BroadcastInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "broadcast_id={} interval={} len={}",
+            self.broadcast_id, self.interval, self.len
+        )
+    }
+}
+
+// Hub -> Server :: Running periodic broadcasts (reply to ListBroadcasts)
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct BroadcastList {
+    pub id: u32,
+    pub rx_id: u32,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub broadcasts: heapless::Vec<BroadcastInfo, MAX_BROADCASTS>,
+}
+
+impl BroadcastList {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.broadcasts.iter().try_for_each(BroadcastInfo::validate)
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl BroadcastList {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        id: u32,
+        rx_id: u32,
+        broadcasts: Vec<BroadcastInfo>,
+    ) -> rquickjs::Result<Self> {
+        let m = Self {
+            id,
+            rx_id,
+            broadcasts: heapless::Vec::from_slice(&broadcasts)
+                .map_err(|_| Exception::throw_message(&ctx, "Too many broadcasts"))?,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "rx_id")]
+    pub fn get_rx_id(&self) -> u32 {
+        self.rx_id
+    }
+    #[qjs(get, rename = "broadcasts")]
+    pub fn get_broadcasts(&self) -> Vec<BroadcastInfo> {
+        self.broadcasts.to_vec()
+    }
+    pub fn debug(&self) -> String {
+        format!("BroadcastList: {:?}", self)
+    }
+}
+
+impl Display for BroadcastList {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[{}] BroadcastList: rx_id={} [", self.id, self.rx_id)?;
+        for (i, b) in self.broadcasts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", b)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl defmt::Format for BroadcastList {
     fn format(&self, fmt: defmt::Formatter) {
         defmt::write!(
             fmt,
-            "[{}] BroadcastData: interval={:?} data=\"{}\"",
+            "[{}] BroadcastList: rx_id={} {}",
             self.id,
-            self.interval,
-            display_vec::<64, MAX_DATA_LEN>(&self.data)
+            self.rx_id,
+            self.broadcasts.as_slice()
         )
     }
 }
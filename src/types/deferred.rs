@@ -0,0 +1,390 @@
+#[cfg(feature = "js")]
+use rquickjs::{class::Trace, ArrayBuffer, Ctx, Exception, JsLifetime};
+
+#[cfg(feature = "js")]
+use crate::util::buf_to_array;
+
+use crate::types::validate::{ValidationError, MAX_PEERS};
+use crate::util::format_mac;
+
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+// Deferred send semantics
+//
+// TxData with defer set is queued by the hub per dst_addr (FIFO) and Acked
+// when queued (Ack DeferQueueFull if the hub queue is full). The queue for a
+// peer is sent when the hub receives a frame from that peer (the peer is
+// awake) - queued messages are sent for HubConfig.wake_window ms after each
+// received frame, anything not sent in the window waits for the next wake.
+// TxData queued while the peer is awake is sent immediately.
+//
+// TxStatus is reported when a deferred message is actually sent. Discarded
+// messages are dropped without TxStatus.
+
+/// Maximum per-peer entries in DeferredStatus (every peer)
+pub const MAX_DEFERRED_PEERS: usize = MAX_PEERS;
+
+// Server -> Hub :: Send deferred messages now (dst_addr None: all peers)
+//
+// Messages are sent whether or not the peer is awake (a sleeping peer will
+// be reported as TxStatus failure).
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct FlushDeferred {
+    pub id: u32,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub dst_addr: Option<[u8; 6]>,
+}
+
+impl FlushDeferred {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl FlushDeferred {
+    #[qjs(constructor)]
+    pub fn new(ctx: Ctx<'_>, id: u32, dst_addr: Option<ArrayBuffer<'_>>) -> rquickjs::Result<Self> {
+        let dst_addr: Option<[u8; 6]> = match dst_addr {
+            Some(buf) => Some(buf_to_array::<6>(&ctx, &buf, "Invalid dst_addr")?),
+            None => None,
+        };
+        Ok(Self { id, dst_addr })
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "dst_addr")]
+    pub fn get_dst_addr<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<Option<ArrayBuffer<'js>>> {
+        self.dst_addr
+            .map(|a| ArrayBuffer::new_copy(ctx, a.as_slice()))
+            .transpose()
+    }
+    pub fn debug(&self) -> String {
+        format!("FlushDeferred: {:?}", self)
+    }
+}
+
+impl Display for FlushDeferred {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.dst_addr {
+            Some(a) => write!(f, "[{}] FlushDeferred: dst={}", self.id, format_mac(a)),
+            None => write!(f, "[{}] FlushDeferred: dst=*", self.id),
+        }
+    }
+}
+
+impl defmt::Format for FlushDeferred {
+    fn format(&self, fmt: defmt::Formatter) {
+        match &self.dst_addr {
+            Some(a) => defmt::write!(fmt, "[{}] FlushDeferred: dst={}", self.id, format_mac(a)),
+            None => defmt::write!(fmt, "[{}] FlushDeferred: dst=*", self.id),
+        }
+    }
+}
+
+// Server -> Hub :: Drop deferred messages for dst_addr
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct DiscardDeferred {
+    pub id: u32,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub dst_addr: [u8; 6],
+}
+
+impl DiscardDeferred {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl DiscardDeferred {
+    #[qjs(constructor)]
+    pub fn new(ctx: Ctx<'_>, id: u32, dst_addr: ArrayBuffer<'_>) -> rquickjs::Result<Self> {
+        Ok(Self {
+            id,
+            dst_addr: buf_to_array::<6>(&ctx, &dst_addr, "Invalid dst_addr")?,
+        })
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "dst_addr")]
+    pub fn get_dst_addr<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<ArrayBuffer<'js>> {
+        ArrayBuffer::new_copy(ctx, self.dst_addr.as_slice())
+    }
+    pub fn debug(&self) -> String {
+        format!("DiscardDeferred: {:?}", self)
+    }
+}
+
+impl Display for DiscardDeferred {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] DiscardDeferred: dst={}",
+            self.id,
+            format_mac(&self.dst_addr)
+        )
+    }
+}
+
+impl defmt::Format for DiscardDeferred {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] DiscardDeferred: dst={}",
+            self.id,
+            format_mac(&self.dst_addr)
+        )
+    }
+}
+
+// Server -> Hub :: Request DeferredStatus
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct GetDeferred {
+    pub id: u32,
+}
+
+impl GetDeferred {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl GetDeferred {
+    #[qjs(constructor)]
+    pub fn new(id: u32) -> Self {
+        Self { id }
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    pub fn debug(&self) -> String {
+        format!("GetDeferred: {:?}", self)
+    }
+}
+
+impl Display for GetDeferred {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[{}] GetDeferred", self.id)
+    }
+}
+
+impl defmt::Format for GetDeferred {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "[{}] GetDeferred", self.id)
+    }
+}
+
+/// Deferred queue for a single peer
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct DeferredDepth {
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub dst_addr: [u8; 6],
+    /// Queued messages
+    pub depth: u8,
+    /// Age of oldest queued message (ms)
+    pub age: u32,
+}
+
+impl DeferredDepth {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl DeferredDepth {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        dst_addr: ArrayBuffer<'_>,
+        depth: u8,
+        age: u32,
+    ) -> rquickjs::Result<Self> {
+        Ok(Self {
+            dst_addr: buf_to_array::<6>(&ctx, &dst_addr, "Invalid dst_addr")?,
+            depth,
+            age,
+        })
+    }
+    #[qjs(get, rename = "dst_addr")]
+    pub fn get_dst_addr<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<ArrayBuffer<'js>> {
+        ArrayBuffer::new_copy(ctx, self.dst_addr.as_slice())
+    }
+    #[qjs(get, rename = "depth")]
+    pub fn get_depth(&self) -> u8 {
+        self.depth
+    }
+    #[qjs(get, rename = "age")]
+    pub fn get_age(&self) -> u32 {
+        self.age
+    }
+    pub fn debug(&self) -> String {
+        format!("DeferredDepth: {:?}", self)
+    }
+}
+
+impl Display for DeferredDepth {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}:{}/{}ms",
+            format_mac(&self.dst_addr),
+            self.depth,
+            self.age
+        )
+    }
+}
+
+impl defmt::Format for DeferredDepth {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "{}:{}/{}ms",
+            format_mac(&self.dst_addr),
+            self.depth,
+            self.age
+        )
+    }
+}
+
+// Hub -> Server :: Deferred queue depth per peer (reply to GetDeferred)
+//
+// Only peers with queued messages are included.
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct DeferredStatus {
+    pub id: u32,
+    pub rx_id: u32,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub queues: heapless::Vec<DeferredDepth, MAX_DEFERRED_PEERS>,
+}
+
+impl DeferredStatus {
+    /// Total queued messages
+    pub fn total(&self) -> usize {
+        self.queues.iter().map(|q| q.depth as usize).sum()
+    }
+
+    /// Queue depth for dst_addr
+    pub fn depth(&self, dst_addr: &[u8; 6]) -> u8 {
+        self.queues
+            .iter()
+            .find(|q| &q.dst_addr == dst_addr)
+            .map(|q| q.depth)
+            .unwrap_or(0)
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl DeferredStatus {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        id: u32,
+        rx_id: u32,
+        queues: Vec<DeferredDepth>,
+    ) -> rquickjs::Result<Self> {
+        Ok(Self {
+            id,
+            rx_id,
+            queues: heapless::Vec::from_slice(&queues)
+                .map_err(|_| Exception::throw_message(&ctx, "Too many queues"))?,
+        })
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "rx_id")]
+    pub fn get_rx_id(&self) -> u32 {
+        self.rx_id
+    }
+    #[qjs(get, rename = "queues")]
+    pub fn get_queues(&self) -> Vec<DeferredDepth> {
+        self.queues.to_vec()
+    }
+    #[qjs(get, rename = "total")]
+    pub fn get_total(&self) -> usize {
+        self.total()
+    }
+    pub fn debug(&self) -> String {
+        format!("DeferredStatus: {:?}", self)
+    }
+}
+
+impl Display for DeferredStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[{}] DeferredStatus: rx_id={} [", self.id, self.rx_id)?;
+        for (i, q) in self.queues.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", q)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl defmt::Format for DeferredStatus {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] DeferredStatus: rx_id={} {}",
+            self.id,
+            self.rx_id,
+            self.queues.as_slice()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_fits_frame() {
+        let queues = (0..MAX_DEFERRED_PEERS)
+            .map(|i| DeferredDepth {
+                dst_addr: [0xff, 0xff, 0xff, 0xff, 0xff, i as u8],
+                depth: u8::MAX,
+                age: u32::MAX,
+            })
+            .collect();
+        let status = DeferredStatus {
+            id: u32::MAX,
+            rx_id: u32::MAX,
+            queues,
+        };
+        assert_eq!(status.total(), MAX_DEFERRED_PEERS * u8::MAX as usize);
+        assert_eq!(status.depth(&[0xff, 0xff, 0xff, 0xff, 0xff, 3]), u8::MAX);
+        assert_eq!(status.depth(&[0; 6]), 0);
+        let msg = crate::Msg::DeferredStatus(status);
+        let mut buf = [0_u8; crate::MAX_MSG_LEN];
+        assert!(msg.to_slice(&mut buf).is_ok());
+    }
+}
@@ -0,0 +1,406 @@
+#[cfg(feature = "js")]
+use rquickjs::{class::Trace, ArrayBuffer, Ctx, Exception, JsLifetime};
+
+#[cfg(feature = "js")]
+use crate::util::buf_to_array;
+
+use crate::types::validate::{ValidationError, MAX_PEERS};
+use crate::types::SendGroupRef;
+use crate::types::TxData;
+use crate::util::format_mac;
+use crate::MAX_DATA_LEN;
+
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+/// Maximum groups per hub
+pub const MAX_GROUPS: usize = 8;
+/// Maximum members per group (every peer)
+pub const MAX_GROUP_MEMBERS: usize = MAX_PEERS;
+
+// Server -> Hub :: Add peer to group / remove peer from group
+//
+// Groups are numbered and created when the first member is added (Ack
+// GroupFull if there are already MAX_GROUPS groups or the group has
+// MAX_GROUP_MEMBERS members). Members must be registered peers (Ack
+// PeerNotFound) and are removed from all groups when the peer is removed.
+// Adding an existing member is a no-op. RemoveFromGroup replies Ack
+// GroupNotFound if the address is not a member.
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct GroupMember {
+    pub id: u32,
+    pub group: u8,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub address: [u8; 6],
+}
+
+impl GroupMember {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl GroupMember {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        id: u32,
+        group: u8,
+        address: ArrayBuffer<'_>,
+    ) -> rquickjs::Result<Self> {
+        Ok(Self {
+            id,
+            group,
+            address: buf_to_array::<6>(&ctx, &address, "Invalid address")?,
+        })
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "group")]
+    pub fn get_group(&self) -> u8 {
+        self.group
+    }
+    #[qjs(get, rename = "address")]
+    pub fn get_address<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<ArrayBuffer<'js>> {
+        ArrayBuffer::new_copy(ctx, self.address.as_slice())
+    }
+    pub fn debug(&self) -> String {
+        format!("GroupMember: {:?}", self)
+    }
+}
+
+impl Display for GroupMember {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] GroupMember: group={} address={}",
+            self.id,
+            self.group,
+            format_mac(&self.address)
+        )
+    }
+}
+
+impl defmt::Format for GroupMember {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] GroupMember: group={} address={}",
+            self.id,
+            self.group,
+            format_mac(&self.address)
+        )
+    }
+}
+
+// Server -> Hub :: Send data to every member of group
+//
+// The hub sends a unicast TxData to each member and replies with Ack (Ack
+// GroupNotFound if the group has no members), followed by GroupStatus with
+// the per-member results once all sends have completed. defer applies to
+// each member (see deferred).
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct SendGroup {
+    pub id: u32,
+    pub group: u8,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub data: heapless::Vec<u8, MAX_DATA_LEN>,
+    pub defer: bool,
+}
+
+impl SendGroup {
+    /// TxData for each member - next_id allocates the Msg id for each frame
+    pub fn fan_out<'a>(
+        &'a self,
+        members: &'a [[u8; 6]],
+        mut next_id: impl FnMut() -> u32 + 'a,
+    ) -> impl Iterator<Item = TxData> + 'a {
+        members.iter().map(move |dst_addr| TxData {
+            id: next_id(),
+            dst_addr: *dst_addr,
+            data: self.data.clone(),
+            defer: self.defer,
+            schedule: None,
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        SendGroupRef::from(self).validate()
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl SendGroup {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        id: u32,
+        group: u8,
+        data: ArrayBuffer<'_>,
+        defer: bool,
+    ) -> rquickjs::Result<Self> {
+        let m = Self {
+            id,
+            group,
+            data: heapless::Vec::from_slice(data.as_bytes().unwrap_or(&[]))
+                .map_err(|_| Exception::throw_message(&ctx, "data invalid"))?,
+            defer,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "group")]
+    pub fn get_group(&self) -> u8 {
+        self.group
+    }
+    #[qjs(get, rename = "data")]
+    pub fn get_data<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<ArrayBuffer<'js>> {
+        ArrayBuffer::new_copy(ctx, self.data.as_slice())
+    }
+    #[qjs(get, rename = "defer")]
+    pub fn get_defer(&self) -> bool {
+        self.defer
+    }
+    pub fn debug(&self) -> String {
+        format!("SendGroup: {:?}", self)
+    }
+}
+
+impl Display for SendGroup {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", SendGroupRef::from(self))
+    }
+}
+
+impl defmt::Format for SendGroup {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{}", SendGroupRef::from(self))
+    }
+}
+
+/// Send result for a single group member
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct MemberResult {
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub address: [u8; 6],
+    pub success: bool,
+}
+
+impl MemberResult {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl MemberResult {
+    #[qjs(constructor)]
+    pub fn new(ctx: Ctx<'_>, address: ArrayBuffer<'_>, success: bool) -> rquickjs::Result<Self> {
+        Ok(Self {
+            address: buf_to_array::<6>(&ctx, &address, "Invalid address")?,
+            success,
+        })
+    }
+    #[qjs(get, rename = "address")]
+    pub fn get_address<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<ArrayBuffer<'js>> {
+        ArrayBuffer::new_copy(ctx, self.address.as_slice())
+    }
+    #[qjs(get, rename = "success")]
+    pub fn get_success(&self) -> bool {
+        self.success
+    }
+    pub fn debug(&self) -> String {
+        format!("MemberResult: {:?}", self)
+    }
+}
+
+impl Display for MemberResult {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}", format_mac(&self.address), self.success)
+    }
+}
+
+impl defmt::Format for MemberResult {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{}:{}", format_mac(&self.address), self.success)
+    }
+}
+
+// Hub -> Server :: Aggregated SendGroup result (rx_id is the SendGroup id)
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct GroupStatus {
+    pub id: u32,
+    pub rx_id: u32,
+    pub group: u8,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub results: heapless::Vec<MemberResult, MAX_GROUP_MEMBERS>,
+}
+
+impl GroupStatus {
+    /// Number of members sent successfully
+    pub fn delivered(&self) -> usize {
+        self.results.iter().filter(|r| r.success).count()
+    }
+
+    /// Members that failed
+    pub fn failed(&self) -> impl Iterator<Item = &[u8; 6]> {
+        self.results
+            .iter()
+            .filter(|r| !r.success)
+            .map(|r| &r.address)
+    }
+
+    pub fn success(&self) -> bool {
+        self.results.iter().all(|r| r.success)
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl GroupStatus {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        id: u32,
+        rx_id: u32,
+        group: u8,
+        results: Vec<MemberResult>,
+    ) -> rquickjs::Result<Self> {
+        Ok(Self {
+            id,
+            rx_id,
+            group,
+            results: heapless::Vec::from_slice(&results)
+                .map_err(|_| Exception::throw_message(&ctx, "Too many results"))?,
+        })
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "rx_id")]
+    pub fn get_rx_id(&self) -> u32 {
+        self.rx_id
+    }
+    #[qjs(get, rename = "group")]
+    pub fn get_group(&self) -> u8 {
+        self.group
+    }
+    #[qjs(get, rename = "results")]
+    pub fn get_results(&self) -> Vec<MemberResult> {
+        self.results.to_vec()
+    }
+    #[qjs(get, rename = "delivered")]
+    pub fn get_delivered(&self) -> usize {
+        self.delivered()
+    }
+    #[qjs(get, rename = "success")]
+    pub fn get_success(&self) -> bool {
+        self.success()
+    }
+    pub fn debug(&self) -> String {
+        format!("GroupStatus: {:?}", self)
+    }
+}
+
+impl Display for GroupStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] GroupStatus: rx_id={} group={} [",
+            self.id, self.rx_id, self.group
+        )?;
+        for (i, r) in self.results.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", r)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl defmt::Format for GroupStatus {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] GroupStatus: rx_id={} group={} {}",
+            self.id,
+            self.rx_id,
+            self.group,
+            self.results.as_slice()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fan_out() {
+        let send = SendGroup {
+            id: 1,
+            group: 2,
+            data: heapless::Vec::from_slice(b"lights off").unwrap(),
+            defer: true,
+        };
+        let members = [[0x01; 6], [0x02; 6], [0x03; 6]];
+        let mut id = 10;
+        let tx = send
+            .fan_out(&members, || {
+                id += 1;
+                id
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tx.len(), 3);
+        assert_eq!(tx[2].id, 13);
+        assert_eq!(tx[2].dst_addr, [0x03; 6]);
+        assert!(tx.iter().all(|t| t.defer && t.data == send.data));
+    }
+
+    #[test]
+    fn test_status_fits_frame() {
+        let status = GroupStatus {
+            id: u32::MAX,
+            rx_id: u32::MAX,
+            group: u8::MAX,
+            results: (0..MAX_GROUP_MEMBERS)
+                .map(|i| MemberResult {
+                    address: [0xff, 0xff, 0xff, 0xff, 0xff, i as u8],
+                    success: i % 2 == 0,
+                })
+                .collect(),
+        };
+        assert_eq!(status.delivered(), MAX_GROUP_MEMBERS / 2);
+        assert_eq!(
+            status.failed().next(),
+            Some(&[0xff, 0xff, 0xff, 0xff, 0xff, 1])
+        );
+        assert!(!status.success());
+        let msg = crate::Msg::GroupStatus(status);
+        let mut buf = [0_u8; crate::MAX_MSG_LEN];
+        assert!(msg.to_slice(&mut buf).is_ok());
+    }
+}
@@ -5,6 +5,7 @@ use rquickjs::{class::Trace, ArrayBuffer, Ctx, Exception, JsLifetime};
 use crate::util::buf_to_array;
 
 use crate::types::rate::WifiPhyRate;
+use crate::types::validate::{check_channel, ValidationError};
 
 use core::fmt::Display;
 use serde::{Deserialize, Serialize};
@@ -16,9 +17,19 @@ pub struct HubConfig {
     pub channel: Option<u8>,
     #[cfg_attr(feature = "js", qjs(skip_trace))]
     pub pmk: Option<[u8; 16]>,
+    /// Deferred send window after a frame is received from a peer (ms)
     pub wake_window: Option<u16>,
     #[cfg_attr(feature = "js", qjs(skip_trace))]
     pub rate: Option<WifiPhyRate>, // This is encoded as the u32 value
+    /// Enable/disable RxData meta (None: unchanged) - optional trailer (see RxData)
+    #[serde(skip)]
+    pub rx_meta: Option<bool>,
+}
+
+impl HubConfig {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.channel.map_or(Ok(()), check_channel)
+    }
 }
 
 #[cfg(feature = "js")]
@@ -32,6 +43,7 @@ impl HubConfig {
         pmk: Option<ArrayBuffer<'_>>,
         wake_window: Option<u16>,
         rate: Option<String>,
+        rx_meta: Option<bool>,
     ) -> rquickjs::Result<Self> {
         let pmk: Option<[u8; 16]> = match pmk {
             Some(buf) => Some(buf_to_array::<16>(&ctx, &buf, "Invalid PMK")?),
@@ -45,13 +57,16 @@ impl HubConfig {
             ),
             None => None,
         };
-        Ok(Self {
+        let m = Self {
             id,
             channel,
             pmk,
             wake_window,
             rate,
-        })
+            rx_meta,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
     }
     pub fn debug(&self) -> String {
         format!("HubConfig: {:?}", self)
@@ -62,8 +77,8 @@ impl Display for HubConfig {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "[{}] HubConfig: channel={:?} pmk={:?} wake_window={:?} rate={:?}",
-            self.id, self.channel, self.pmk, self.wake_window, self.rate
+            "[{}] HubConfig: channel={:?} pmk={:?} wake_window={:?} rate={:?} rx_meta={:?}",
+            self.id, self.channel, self.pmk, self.wake_window, self.rate, self.rx_meta
         )
     }
 }
@@ -72,12 +87,13 @@ impl defmt::Format for HubConfig {
     fn format(&self, fmt: defmt::Formatter) {
         defmt::write!(
             fmt,
-            "[{}] HubConfig: channel={:?} pmk={:?} wake_window={:?} rate={:?}",
+            "[{}] HubConfig: channel={:?} pmk={:?} wake_window={:?} rate={:?} rx_meta={:?}",
             self.id,
             self.channel,
             self.pmk,
             self.wake_window,
-            self.rate
+            self.rate,
+            self.rx_meta
         )
     }
 }
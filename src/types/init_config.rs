@@ -4,6 +4,7 @@ use rquickjs::{class::Trace, ArrayBuffer, Ctx, JsLifetime};
 #[cfg(feature = "js")]
 use crate::util::buf_to_array;
 
+use crate::types::validate::{check_channel, ValidationError};
 use crate::util::format_mac;
 
 use core::fmt::Display;
@@ -22,6 +23,12 @@ pub struct InitConfig {
     pub address: [u8; 6],
 }
 
+impl InitConfig {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        check_channel(self.channel)
+    }
+}
+
 #[cfg(feature = "js")]
 #[rquickjs::methods]
 impl InitConfig {
@@ -34,13 +41,15 @@ impl InitConfig {
         channel: u8,
         address: ArrayBuffer<'_>,
     ) -> rquickjs::Result<Self> {
-        Ok(Self {
+        let m = Self {
             id,
             api_version,
             now_version,
             channel,
             address: buf_to_array::<6>(&ctx, &address, "Invalid address")?,
-        })
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
     }
     pub fn debug(&self) -> String {
         format!("InitConfig: {:?}", self)
@@ -1,18 +1,45 @@
 pub mod ack;
 pub mod broadcast;
+pub mod deferred;
+pub mod group;
 pub mod hub_config;
 pub mod init_config;
 pub mod msg;
+pub mod msg_ref;
 pub mod peer;
+pub mod peer_list;
+pub mod ping;
 pub mod rate;
 pub mod rx_data;
+pub mod rx_filter;
+pub mod stats;
 pub mod tx_data;
+pub mod tx_status;
+pub mod unknown;
+pub mod validate;
 
-pub use ack::Ack;
-pub use broadcast::BroadcastData;
+pub use ack::{Ack, AckError};
+pub use broadcast::{
+    BroadcastData, BroadcastInfo, BroadcastList, ListBroadcasts, StopBroadcast, MAX_BROADCASTS,
+    MIN_BROADCAST_INTERVAL,
+};
+pub use deferred::{
+    DeferredDepth, DeferredStatus, DiscardDeferred, FlushDeferred, GetDeferred, MAX_DEFERRED_PEERS,
+};
+pub use group::{GroupMember, GroupStatus, MemberResult, SendGroup, MAX_GROUPS, MAX_GROUP_MEMBERS};
 pub use hub_config::HubConfig;
 pub use init_config::InitConfig;
-pub use msg::Msg;
+pub use msg::{Msg, MsgError};
+pub use msg_ref::{
+    BodyRef, BroadcastDataRef, MsgRef, RxDataRef, SendGroupRef, TxDataRef, UnknownMsgRef,
+};
 pub use peer::{PeerAddress, PeerInfo};
-pub use rx_data::RxData;
-pub use tx_data::TxData;
+pub use peer_list::{ListPeers, PeerList, PEER_LIST_LEN};
+pub use ping::{Ping, Pong};
+pub use rx_data::{RxData, RxMeta};
+pub use rx_filter::{RxFilter, RxFilterRule, MAX_RX_FILTER_PREFIX, MAX_RX_FILTER_RULES};
+pub use stats::{GetStats, HubCounters, HubStats};
+pub use tx_data::{CancelSend, Schedule, TxData};
+pub use tx_status::TxStatus;
+pub use unknown::UnknownMsg;
+pub use validate::{InvalidMsg, ValidationError, MAX_CHANNEL, MAX_PEERS};
@@ -1,14 +1,24 @@
 #[cfg(feature = "js")]
 use rquickjs::{class::Trace, Class, Ctx, Exception, JsLifetime, Value};
 
+use crate::types::validate::{InvalidMsg, ValidationError};
 use crate::types::{
-    Ack, BroadcastData, HubConfig, InitConfig, PeerAddress, PeerInfo, RxData, TxData,
+    Ack, BroadcastData, BroadcastList, CancelSend, DeferredStatus, DiscardDeferred, FlushDeferred,
+    GetDeferred, GetStats, GroupMember, GroupStatus, HubConfig, HubStats, InitConfig,
+    ListBroadcasts, ListPeers, MsgRef, PeerAddress, PeerInfo, PeerList, Ping, Pong, RxData,
+    RxFilter, SendGroup, StopBroadcast, TxData, TxStatus, UnknownMsg,
 };
 
+use crate::{MAX_DATA_LEN, MAX_MSG_LEN, PEER_LIST_LEN};
+
 use core::fmt::Display;
-use serde::{Deserialize, Serialize};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{ser, Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+/// Protocol message
+///
+/// Serialises (serde) as its wire envelope - see to_slice/from_slice.
+#[derive(Clone, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
 pub enum Msg {
     Init(InitConfig),
@@ -20,6 +30,28 @@ pub enum Msg {
     ModifyPeer(PeerInfo),
     RemovePeer(PeerAddress),
     Ack(Ack),
+    Unknown(UnknownMsg),
+    Ping(Ping),
+    Pong(Pong),
+    GetStats(GetStats),
+    HubStats(HubStats),
+    ListPeers(ListPeers),
+    PeerList(PeerList),
+    GetPeer(PeerAddress),
+    TxStatus(TxStatus),
+    StopBroadcast(StopBroadcast),
+    ListBroadcasts(ListBroadcasts),
+    BroadcastList(BroadcastList),
+    FlushDeferred(FlushDeferred),
+    DiscardDeferred(DiscardDeferred),
+    GetDeferred(GetDeferred),
+    DeferredStatus(DeferredStatus),
+    CancelSend(CancelSend),
+    AddToGroup(GroupMember),
+    RemoveFromGroup(GroupMember),
+    SendGroup(SendGroup),
+    GroupStatus(GroupStatus),
+    SetRxFilter(RxFilter),
 }
 
 /// Cast object as Msg class
@@ -54,6 +86,28 @@ impl Msg {
             "ModifyPeer" => extract_class!(&ctx, &o, ModifyPeer, PeerInfo),
             "RemovePeer" => extract_class!(&ctx, &o, RemovePeer, PeerAddress),
             "Ack" => extract_class!(&ctx, &o, Ack, Ack),
+            "Unknown" => extract_class!(&ctx, &o, Unknown, UnknownMsg),
+            "Ping" => extract_class!(&ctx, &o, Ping, Ping),
+            "Pong" => extract_class!(&ctx, &o, Pong, Pong),
+            "GetStats" => extract_class!(&ctx, &o, GetStats, GetStats),
+            "HubStats" => extract_class!(&ctx, &o, HubStats, HubStats),
+            "ListPeers" => extract_class!(&ctx, &o, ListPeers, ListPeers),
+            "PeerList" => extract_class!(&ctx, &o, PeerList, PeerList),
+            "GetPeer" => extract_class!(&ctx, &o, GetPeer, PeerAddress),
+            "TxStatus" => extract_class!(&ctx, &o, TxStatus, TxStatus),
+            "StopBroadcast" => extract_class!(&ctx, &o, StopBroadcast, StopBroadcast),
+            "ListBroadcasts" => extract_class!(&ctx, &o, ListBroadcasts, ListBroadcasts),
+            "BroadcastList" => extract_class!(&ctx, &o, BroadcastList, BroadcastList),
+            "FlushDeferred" => extract_class!(&ctx, &o, FlushDeferred, FlushDeferred),
+            "DiscardDeferred" => extract_class!(&ctx, &o, DiscardDeferred, DiscardDeferred),
+            "GetDeferred" => extract_class!(&ctx, &o, GetDeferred, GetDeferred),
+            "DeferredStatus" => extract_class!(&ctx, &o, DeferredStatus, DeferredStatus),
+            "CancelSend" => extract_class!(&ctx, &o, CancelSend, CancelSend),
+            "AddToGroup" => extract_class!(&ctx, &o, AddToGroup, GroupMember),
+            "RemoveFromGroup" => extract_class!(&ctx, &o, RemoveFromGroup, GroupMember),
+            "SendGroup" => extract_class!(&ctx, &o, SendGroup, SendGroup),
+            "GroupStatus" => extract_class!(&ctx, &o, GroupStatus, GroupStatus),
+            "SetRxFilter" => extract_class!(&ctx, &o, SetRxFilter, RxFilter),
             _ => Err(Exception::throw_message(&ctx, "Invalid Msg type")),
         }
     }
@@ -86,13 +140,15 @@ impl Msg {
     }
 
     #[qjs(static, rename = "AddPeer")]
-    pub fn new_add_peer(peer: PeerInfo) -> Self {
-        Msg::AddPeer(peer)
+    pub fn new_add_peer(ctx: Ctx<'_>, peer: PeerInfo) -> rquickjs::Result<Self> {
+        peer.validate_request().map_err(|e| e.throw(&ctx))?;
+        Ok(Msg::AddPeer(peer))
     }
 
     #[qjs(static, rename = "ModifyPeer")]
-    pub fn new_modify_peer(peer: PeerInfo) -> Self {
-        Msg::ModifyPeer(peer)
+    pub fn new_modify_peer(ctx: Ctx<'_>, peer: PeerInfo) -> rquickjs::Result<Self> {
+        peer.validate_request().map_err(|e| e.throw(&ctx))?;
+        Ok(Msg::ModifyPeer(peer))
     }
 
     #[qjs(static, rename = "RemovePeer")]
@@ -105,6 +161,116 @@ impl Msg {
         Msg::Ack(ack)
     }
 
+    #[qjs(static, rename = "Unknown")]
+    pub fn new_unknown(unknown: UnknownMsg) -> Self {
+        Msg::Unknown(unknown)
+    }
+
+    #[qjs(static, rename = "Ping")]
+    pub fn new_ping(ping: Ping) -> Self {
+        Msg::Ping(ping)
+    }
+
+    #[qjs(static, rename = "Pong")]
+    pub fn new_pong(pong: Pong) -> Self {
+        Msg::Pong(pong)
+    }
+
+    #[qjs(static, rename = "GetStats")]
+    pub fn new_get_stats(get_stats: GetStats) -> Self {
+        Msg::GetStats(get_stats)
+    }
+
+    #[qjs(static, rename = "HubStats")]
+    pub fn new_hub_stats(stats: HubStats) -> Self {
+        Msg::HubStats(stats)
+    }
+
+    #[qjs(static, rename = "ListPeers")]
+    pub fn new_list_peers(list_peers: ListPeers) -> Self {
+        Msg::ListPeers(list_peers)
+    }
+
+    #[qjs(static, rename = "PeerList")]
+    pub fn new_peer_list(peer_list: PeerList) -> Self {
+        Msg::PeerList(peer_list)
+    }
+
+    #[qjs(static, rename = "GetPeer")]
+    pub fn new_get_peer(peer: PeerAddress) -> Self {
+        Msg::GetPeer(peer)
+    }
+
+    #[qjs(static, rename = "TxStatus")]
+    pub fn new_tx_status(status: TxStatus) -> Self {
+        Msg::TxStatus(status)
+    }
+
+    #[qjs(static, rename = "StopBroadcast")]
+    pub fn new_stop_broadcast(stop: StopBroadcast) -> Self {
+        Msg::StopBroadcast(stop)
+    }
+
+    #[qjs(static, rename = "ListBroadcasts")]
+    pub fn new_list_broadcasts(list_broadcasts: ListBroadcasts) -> Self {
+        Msg::ListBroadcasts(list_broadcasts)
+    }
+
+    #[qjs(static, rename = "BroadcastList")]
+    pub fn new_broadcast_list(broadcast_list: BroadcastList) -> Self {
+        Msg::BroadcastList(broadcast_list)
+    }
+
+    #[qjs(static, rename = "FlushDeferred")]
+    pub fn new_flush_deferred(flush_deferred: FlushDeferred) -> Self {
+        Msg::FlushDeferred(flush_deferred)
+    }
+
+    #[qjs(static, rename = "DiscardDeferred")]
+    pub fn new_discard_deferred(discard_deferred: DiscardDeferred) -> Self {
+        Msg::DiscardDeferred(discard_deferred)
+    }
+
+    #[qjs(static, rename = "GetDeferred")]
+    pub fn new_get_deferred(get_deferred: GetDeferred) -> Self {
+        Msg::GetDeferred(get_deferred)
+    }
+
+    #[qjs(static, rename = "DeferredStatus")]
+    pub fn new_deferred_status(deferred_status: DeferredStatus) -> Self {
+        Msg::DeferredStatus(deferred_status)
+    }
+
+    #[qjs(static, rename = "CancelSend")]
+    pub fn new_cancel_send(cancel_send: CancelSend) -> Self {
+        Msg::CancelSend(cancel_send)
+    }
+
+    #[qjs(static, rename = "AddToGroup")]
+    pub fn new_add_to_group(add_to_group: GroupMember) -> Self {
+        Msg::AddToGroup(add_to_group)
+    }
+
+    #[qjs(static, rename = "RemoveFromGroup")]
+    pub fn new_remove_from_group(remove_from_group: GroupMember) -> Self {
+        Msg::RemoveFromGroup(remove_from_group)
+    }
+
+    #[qjs(static, rename = "SendGroup")]
+    pub fn new_send_group(send_group: SendGroup) -> Self {
+        Msg::SendGroup(send_group)
+    }
+
+    #[qjs(static, rename = "GroupStatus")]
+    pub fn new_group_status(group_status: GroupStatus) -> Self {
+        Msg::GroupStatus(group_status)
+    }
+
+    #[qjs(static, rename = "SetRxFilter")]
+    pub fn new_set_rx_filter(set_rx_filter: RxFilter) -> Self {
+        Msg::SetRxFilter(set_rx_filter)
+    }
+
     #[qjs(get, rename = "type")]
     pub fn get_type(&self) -> String {
         match &self {
@@ -117,6 +283,28 @@ impl Msg {
             Msg::ModifyPeer(_) => "ModifyPeer",
             Msg::RemovePeer(_) => "RemovePeer",
             Msg::Ack(_) => "Ack",
+            Msg::Unknown(_) => "Unknown",
+            Msg::Ping(_) => "Ping",
+            Msg::Pong(_) => "Pong",
+            Msg::GetStats(_) => "GetStats",
+            Msg::HubStats(_) => "HubStats",
+            Msg::ListPeers(_) => "ListPeers",
+            Msg::PeerList(_) => "PeerList",
+            Msg::GetPeer(_) => "GetPeer",
+            Msg::TxStatus(_) => "TxStatus",
+            Msg::StopBroadcast(_) => "StopBroadcast",
+            Msg::ListBroadcasts(_) => "ListBroadcasts",
+            Msg::BroadcastList(_) => "BroadcastList",
+            Msg::FlushDeferred(_) => "FlushDeferred",
+            Msg::DiscardDeferred(_) => "DiscardDeferred",
+            Msg::GetDeferred(_) => "GetDeferred",
+            Msg::DeferredStatus(_) => "DeferredStatus",
+            Msg::CancelSend(_) => "CancelSend",
+            Msg::AddToGroup(_) => "AddToGroup",
+            Msg::RemoveFromGroup(_) => "RemoveFromGroup",
+            Msg::SendGroup(_) => "SendGroup",
+            Msg::GroupStatus(_) => "GroupStatus",
+            Msg::SetRxFilter(_) => "SetRxFilter",
         }
         .to_string()
     }
@@ -138,6 +326,28 @@ impl Msg {
             Msg::ModifyPeer(m) => Class::instance(ctx, m.clone())?.into_value(),
             Msg::RemovePeer(m) => Class::instance(ctx, m.clone())?.into_value(),
             Msg::Ack(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::Unknown(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::Ping(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::Pong(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::GetStats(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::HubStats(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::ListPeers(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::PeerList(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::GetPeer(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::TxStatus(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::StopBroadcast(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::ListBroadcasts(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::BroadcastList(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::FlushDeferred(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::DiscardDeferred(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::GetDeferred(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::DeferredStatus(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::CancelSend(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::AddToGroup(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::RemoveFromGroup(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::SendGroup(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::GroupStatus(m) => Class::instance(ctx, m.clone())?.into_value(),
+            Msg::SetRxFilter(m) => Class::instance(ctx, m.clone())?.into_value(),
         })
     }
 
@@ -148,70 +358,265 @@ impl Msg {
 
 impl Msg {
     pub fn get_id(&self) -> u32 {
+        MsgRef::from(self).get_id()
+    }
+
+    /// ESP-NOW payload length (for messages carrying a payload)
+    pub fn data_len(&self) -> Option<usize> {
+        MsgRef::from(self).data_len()
+    }
+
+    /// Protocol api_version which introduced this message (or the optional
+    /// trailer it carries - older hubs ignore trailers)
+    pub fn api_version(&self) -> u32 {
         match self {
-            Msg::Init(m) => m.id,
-            Msg::HubConfig(m) => m.id,
-            Msg::Send(m) => m.id,
-            Msg::Recv(m) => m.id,
-            Msg::Broadcast(m) => m.id,
-            Msg::AddPeer(m) => m.id,
-            Msg::ModifyPeer(m) => m.id,
-            Msg::RemovePeer(m) => m.id,
-            Msg::Ack(m) => m.id,
+            Msg::HubConfig(m) if m.rx_meta.is_some() => 2,
+            Msg::Broadcast(m) if m.replace.is_some() => 2,
+            Msg::Send(m) if m.schedule.is_some() => 2,
+            Msg::Init(_)
+            | Msg::HubConfig(_)
+            | Msg::Send(_)
+            | Msg::Recv(_)
+            | Msg::Broadcast(_)
+            | Msg::AddPeer(_)
+            | Msg::ModifyPeer(_)
+            | Msg::RemovePeer(_)
+            | Msg::Ack(_)
+            | Msg::Unknown(_) => 1,
+            Msg::Ping(_)
+            | Msg::Pong(_)
+            | Msg::GetStats(_)
+            | Msg::HubStats(_)
+            | Msg::ListPeers(_)
+            | Msg::PeerList(_)
+            | Msg::GetPeer(_)
+            | Msg::TxStatus(_)
+            | Msg::StopBroadcast(_)
+            | Msg::ListBroadcasts(_)
+            | Msg::BroadcastList(_)
+            | Msg::FlushDeferred(_)
+            | Msg::DiscardDeferred(_)
+            | Msg::GetDeferred(_)
+            | Msg::DeferredStatus(_)
+            | Msg::CancelSend(_)
+            | Msg::AddToGroup(_)
+            | Msg::RemoveFromGroup(_)
+            | Msg::SendGroup(_)
+            | Msg::GroupStatus(_)
+            | Msg::SetRxFilter(_) => 2,
         }
     }
+
+    /// Semantic validation of the contained type
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        MsgRef::from(self).validate()
+    }
+
+    /// Decode and validate a received Msg
+    ///
+    /// Decode errors and invalid messages are returned as the AckError to
+    /// report (with the Msg id if it can be recovered).
+    pub fn from_slice_validated(buf: &[u8]) -> Result<Self, InvalidMsg> {
+        let msg = MsgRef::from_slice_validated(buf)?;
+        msg.to_msg().map_err(|e| InvalidMsg {
+            id: Some(msg.get_id()),
+            error: e.into(),
+        })
+    }
 }
 
 impl Display for Msg {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            Msg::Init(m) => write!(f, "{}", m),
-            Msg::HubConfig(m) => write!(f, "{}", m),
-            Msg::Send(m) => write!(f, "{}", m),
-            Msg::Recv(m) => write!(f, "{}", m),
-            Msg::Broadcast(m) => write!(f, "{}", m),
-            Msg::AddPeer(m) => write!(f, "{}", m),
-            Msg::ModifyPeer(m) => write!(f, "{}", m),
-            Msg::RemovePeer(m) => write!(f, "{}", m),
-            Msg::Ack(m) => write!(f, "{}", m),
-        }
+        write!(f, "{}", MsgRef::from(self))
     }
 }
 
 impl defmt::Format for Msg {
     fn format(&self, fmt: defmt::Formatter) {
-        match self {
-            Msg::Init(m) => defmt::write!(fmt, "{}", m),
-            Msg::HubConfig(m) => defmt::write!(fmt, "{}", m),
-            Msg::Send(m) => defmt::write!(fmt, "{}", m),
-            Msg::Recv(m) => defmt::write!(fmt, "{}", m),
-            Msg::Broadcast(m) => defmt::write!(fmt, "{}", m),
-            Msg::AddPeer(m) => defmt::write!(fmt, "{}", m),
-            Msg::ModifyPeer(m) => defmt::write!(fmt, "{}", m),
-            Msg::RemovePeer(m) => defmt::write!(fmt, "{}", m),
-            Msg::Ack(m) => defmt::write!(fmt, "{}", m),
-        }
+        defmt::write!(fmt, "{}", MsgRef::from(self))
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, defmt::Format)]
 pub enum MsgError {
     PostcardError,
     CapacityError,
+    FramingError,
+    ChecksumError,
+}
+
+impl Display for MsgError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s = match self {
+            MsgError::PostcardError => "postcard decode error",
+            MsgError::CapacityError => "capacity exceeded",
+            MsgError::FramingError => "invalid frame",
+            MsgError::ChecksumError => "checksum mismatch",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MsgError {}
+
+#[cfg(not(feature = "std"))]
+impl core::error::Error for MsgError {}
+
+// Wire envelope :: [kind: u8][len: varint][body: postcard(inner struct)]
+//
+// The length prefix lets a decoder skip the body of a message kind it does not
+// know (surfaced as Msg::Unknown) and ignore trailing fields appended to a
+// known body by newer versions. Every body starts with the message id (varint)
+// so that unknown messages can still be acknowledged.
+
+/// Largest varint encoding of a u32/i32
+pub(crate) const VARINT_MAX_LEN: usize = 5;
+pub(crate) const ENVELOPE_HEADER_LEN: usize = 1 + VARINT_MAX_LEN;
+
+// Largest bodies :: every varint at its maximum length and every trailer present
+
+/// RxData: id, src/dst address, data (length < 2^14), rssi, RxMeta trailer
+/// (channel, noise_floor, rate, sig_mode, timestamp)
+pub(crate) const RX_DATA_MAX_LEN: usize =
+    VARINT_MAX_LEN + 6 + 6 + 2 + MAX_DATA_LEN + VARINT_MAX_LEN + 4 + VARINT_MAX_LEN;
+/// PeerList: id, rx_id, offset, total, PEER_LIST_LEN peers (id, address, lmk,
+/// channel, encrypt)
+pub(crate) const PEER_LIST_MAX_LEN: usize =
+    2 * VARINT_MAX_LEN + 2 + 1 + PEER_LIST_LEN * (VARINT_MAX_LEN + 6 + 17 + 2 + 1);
+
+/// Split envelope into kind and body
+pub(crate) fn decode_envelope(buf: &[u8]) -> Result<(u8, &[u8]), MsgError> {
+    let (&kind, rest) = buf.split_first().ok_or(MsgError::PostcardError)?;
+    let (len, rest) =
+        postcard::take_from_bytes::<u32>(rest).map_err(|_| MsgError::PostcardError)?;
+    let body = rest.get(..len as usize).ok_or(MsgError::PostcardError)?;
+    Ok((kind, body))
+}
+
+/// Write envelope to buf - body is written by f which returns the body length
+pub(crate) fn encode_envelope<F>(kind: u8, buf: &mut [u8], f: F) -> Result<&mut [u8], MsgError>
+where
+    F: FnOnce(&mut [u8]) -> Result<usize, MsgError>,
+{
+    if buf.len() < ENVELOPE_HEADER_LEN {
+        return Err(MsgError::CapacityError);
+    }
+    let len = f(&mut buf[ENVELOPE_HEADER_LEN..])?;
+    // Write header and close gap left by short length varint
+    buf[0] = kind;
+    let n = postcard::to_slice(&(len as u32), &mut buf[1..ENVELOPE_HEADER_LEN])
+        .map_err(|_| MsgError::PostcardError)?
+        .len();
+    buf.copy_within(ENVELOPE_HEADER_LEN..ENVELOPE_HEADER_LEN + len, 1 + n);
+    Ok(&mut buf[..1 + n + len])
+}
+
+/// Encode postcard body into buf
+pub(crate) fn encode_body<T: Serialize>(body: &T, buf: &mut [u8]) -> Result<usize, MsgError> {
+    Ok(postcard::to_slice(body, buf)
+        .map_err(|_| MsgError::PostcardError)?
+        .len())
+}
+
+// Optional trailer :: field appended to the body of an existing message.
+// Absent when None (or encoded by an older version) so the body stays
+// decodable in both directions.
+
+/// Decode body followed by optional trailer
+pub(crate) fn decode_with_trailer<'a, T, U>(body: &'a [u8]) -> Result<(T, Option<U>), MsgError>
+where
+    T: Deserialize<'a>,
+    U: Deserialize<'a>,
+{
+    let (m, rest) = postcard::take_from_bytes::<T>(body).map_err(|_| MsgError::PostcardError)?;
+    if rest.is_empty() {
+        return Ok((m, None));
+    }
+    let (trailer, _) = postcard::take_from_bytes::<U>(rest).map_err(|_| MsgError::PostcardError)?;
+    Ok((m, Some(trailer)))
+}
+
+/// Encode body followed by optional trailer
+pub(crate) fn encode_with_trailer<T: Serialize, U: Serialize>(
+    body: &T,
+    trailer: &Option<U>,
+    buf: &mut [u8],
+) -> Result<usize, MsgError> {
+    let len = encode_body(body, buf)?;
+    match trailer {
+        Some(t) => Ok(len + encode_body(t, &mut buf[len..])?),
+        None => Ok(len),
+    }
+}
+
+/// Copy raw body into buf
+pub(crate) fn encode_raw(body: &[u8], buf: &mut [u8]) -> Result<usize, MsgError> {
+    buf.get_mut(..body.len())
+        .ok_or(MsgError::CapacityError)?
+        .copy_from_slice(body);
+    Ok(body.len())
 }
 
-/// Postcard serialisation helpers for Msg
+impl Msg {
+    /// Envelope type tag
+    pub fn kind(&self) -> u8 {
+        MsgRef::from(self).kind()
+    }
+}
+
+/// Wire serialisation helpers for Msg (the codec is implemented by MsgRef)
 impl Msg {
     pub fn from_slice(buf: &[u8]) -> Result<Self, MsgError> {
-        postcard::from_bytes::<Self>(buf).map_err(|_| MsgError::PostcardError)
+        MsgRef::from_slice(buf)?.to_msg()
     }
     pub fn to_slice<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut [u8], MsgError> {
-        postcard::to_slice(&self, buf).map_err(|_| MsgError::PostcardError)
+        MsgRef::from(self).to_slice(buf)
     }
-    // Workaround to get heapless::Vec as postcard imports heapless 0.7.17 [vs 0.9.2]
     pub fn to_heapless<const N: usize>(&self) -> Result<heapless::Vec<u8, N>, MsgError> {
-        let mut buf = [0_u8; N];
-        let s = postcard::to_slice(&self, &mut buf).map_err(|_| MsgError::PostcardError)?;
-        Ok(heapless::Vec::<u8, N>::from_slice(&s).map_err(|_| MsgError::CapacityError)?)
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let s = self.to_slice(&mut buf)?;
+        heapless::Vec::<u8, N>::from_slice(s).map_err(|_| MsgError::CapacityError)
+    }
+}
+
+// Serde :: Msg is embedded in other records (eg. Monitor) as its wire envelope
+// (serialized as bytes). A derived encoding would skip the trailer fields.
+
+impl Serialize for Msg {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let encoded = self.to_slice(&mut buf).map_err(ser::Error::custom)?;
+        serializer.serialize_bytes(encoded)
+    }
+}
+
+impl<'de> Deserialize<'de> for Msg {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(MsgVisitor)
+    }
+}
+
+struct MsgVisitor;
+
+impl<'de> Visitor<'de> for MsgVisitor {
+    type Value = Msg;
+
+    fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Msg envelope bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Msg, E> {
+        Msg::from_slice(v).map_err(de::Error::custom)
+    }
+
+    // Formats without a native bytes type
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Msg, A::Error> {
+        let mut buf = heapless::Vec::<u8, MAX_MSG_LEN>::new();
+        while let Some(b) = seq.next_element()? {
+            buf.push(b)
+                .map_err(|_| de::Error::custom(MsgError::CapacityError))?;
+        }
+        self.visit_bytes(&buf)
     }
 }
@@ -0,0 +1,1024 @@
+use crate::types::msg::{
+    decode_envelope, decode_with_trailer, encode_body, encode_envelope, encode_raw,
+    encode_with_trailer,
+};
+use crate::types::validate::{check_data, check_interval, InvalidMsg, ValidationError};
+use crate::types::{
+    Ack, BroadcastData, BroadcastList, CancelSend, DeferredStatus, DiscardDeferred, FlushDeferred,
+    GetDeferred, GetStats, GroupMember, GroupStatus, HubConfig, HubStats, InitConfig,
+    ListBroadcasts, ListPeers, Msg, MsgError, PeerAddress, PeerInfo, PeerList, Ping, Pong, RxData,
+    RxFilter, RxMeta, Schedule, SendGroup, StopBroadcast, TxData, TxStatus, UnknownMsg,
+};
+use crate::util::{display_slice, format_mac};
+use crate::{MAX_DATA_LEN, MAX_MSG_LEN};
+
+use core::fmt::Display;
+use serde::{ser, Deserialize, Serialize, Serializer};
+
+// Borrowed Msg views :: decode/encode without copying payloads
+//
+// Payload fields borrow from the receive buffer (or the ESP-NOW callback
+// data) rather than being copied into a heapless::Vec. The serialised form
+// is identical to the owned types - postcard encodes &[u8] and
+// heapless::Vec<u8, N> the same way - so either can be used on each side of
+// the link.
+//
+// >>> let msg = MsgRef::from_slice(&frame)?;
+// >>> if let MsgRef::Send(tx) = msg { espnow.send(&tx.dst_addr, tx.data) }
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct TxDataRef<'a> {
+    pub id: u32,
+    pub dst_addr: [u8; 6],
+    pub data: &'a [u8],
+    pub defer: bool,
+    #[serde(skip)]
+    pub schedule: Option<Schedule>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct RxDataRef<'a> {
+    pub id: u32,
+    pub src_addr: [u8; 6],
+    pub dst_addr: [u8; 6],
+    pub data: &'a [u8],
+    pub rssi: i32,
+    #[serde(skip)]
+    pub meta: Option<RxMeta>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct BroadcastDataRef<'a> {
+    pub id: u32,
+    pub data: &'a [u8],
+    pub interval: Option<u32>,
+    #[serde(skip)]
+    pub replace: Option<u32>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct SendGroupRef<'a> {
+    pub id: u32,
+    pub group: u8,
+    pub data: &'a [u8],
+    pub defer: bool,
+}
+
+/// Body of a message with unknown envelope kind
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+pub struct UnknownMsgRef<'a> {
+    pub id: u32,
+    pub kind: u8,
+    pub bytes: &'a [u8],
+}
+
+/// Borrowed body of a table message (PeerList, BroadcastList, DeferredStatus,
+/// GroupStatus, RxFilter)
+///
+/// These are too large to copy into MsgRef so the body is either a reference
+/// to the owned value or the encoded body, which is decoded on access. Encoded
+/// bodies are only created by MsgRef::from_slice after they have decoded
+/// successfully.
+#[derive(Debug)]
+pub struct BodyRef<'a, T> {
+    pub id: u32,
+    body: Body<'a, T>,
+}
+
+#[derive(Debug)]
+enum Body<'a, T> {
+    Owned(&'a T),
+    Encoded(&'a [u8]),
+}
+
+impl<T> Clone for Body<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Body<'_, T> {}
+
+impl<T> Clone for BodyRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BodyRef<'_, T> {}
+
+impl<'a, T> BodyRef<'a, T> {
+    fn new(id: u32, m: &'a T) -> Self {
+        Self {
+            id,
+            body: Body::Owned(m),
+        }
+    }
+}
+
+impl<'a, T: Deserialize<'a>> BodyRef<'a, T> {
+    fn decode(body: &'a [u8]) -> Result<Self, MsgError> {
+        postcard::from_bytes::<T>(body).map_err(|_| MsgError::PostcardError)?;
+        Ok(Self {
+            id: postcard::from_bytes(body).map_err(|_| MsgError::PostcardError)?,
+            body: Body::Encoded(body),
+        })
+    }
+
+    /// Call f with the body (decoding it if necessary)
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, MsgError> {
+        match self.body {
+            Body::Owned(m) => Ok(f(m)),
+            Body::Encoded(body) => postcard::from_bytes(body)
+                .map(|m| f(&m))
+                .map_err(|_| MsgError::PostcardError),
+        }
+    }
+
+    /// Copy into the owned type
+    pub fn get(&self) -> Result<T, MsgError>
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, MsgError>
+    where
+        T: Serialize,
+    {
+        match self.body {
+            Body::Owned(m) => encode_body(m, buf),
+            Body::Encoded(body) => encode_raw(body, buf),
+        }
+    }
+}
+
+impl<'a, T: Deserialize<'a> + PartialEq> PartialEq for BodyRef<'a, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.with(|a| other.with(|b| a == b)) == Ok(Ok(true))
+    }
+}
+
+impl<'a, T: Deserialize<'a> + Eq> Eq for BodyRef<'a, T> {}
+
+/// Highest envelope kind known to this version (higher kinds decode as Unknown)
+pub(crate) const MAX_KIND: u8 = 29;
+
+/// Borrowed equivalent of Msg
+///
+/// Variants without a payload hold the (small) owned type, apart from the
+/// table messages which hold a BodyRef. Serialises
+/// identically to Msg (as the wire envelope) so can be used in place of it
+/// (see MonitorRef).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MsgRef<'a> {
+    Init(InitConfig),
+    HubConfig(HubConfig),
+    Send(TxDataRef<'a>),
+    Recv(RxDataRef<'a>),
+    Broadcast(BroadcastDataRef<'a>),
+    AddPeer(PeerInfo),
+    ModifyPeer(PeerInfo),
+    RemovePeer(PeerAddress),
+    Ack(Ack),
+    Unknown(UnknownMsgRef<'a>),
+    Ping(Ping),
+    Pong(Pong),
+    GetStats(GetStats),
+    HubStats(HubStats),
+    ListPeers(ListPeers),
+    PeerList(BodyRef<'a, PeerList>),
+    GetPeer(PeerAddress),
+    TxStatus(TxStatus),
+    StopBroadcast(StopBroadcast),
+    ListBroadcasts(ListBroadcasts),
+    BroadcastList(BodyRef<'a, BroadcastList>),
+    FlushDeferred(FlushDeferred),
+    DiscardDeferred(DiscardDeferred),
+    GetDeferred(GetDeferred),
+    DeferredStatus(BodyRef<'a, DeferredStatus>),
+    CancelSend(CancelSend),
+    AddToGroup(GroupMember),
+    RemoveFromGroup(GroupMember),
+    SendGroup(SendGroupRef<'a>),
+    GroupStatus(BodyRef<'a, GroupStatus>),
+    SetRxFilter(BodyRef<'a, RxFilter>),
+}
+
+impl<'a> MsgRef<'a> {
+    /// Envelope type tag
+    pub fn kind(&self) -> u8 {
+        match self {
+            MsgRef::Init(_) => 0,
+            MsgRef::HubConfig(_) => 1,
+            MsgRef::Send(_) => 2,
+            MsgRef::Recv(_) => 3,
+            MsgRef::Broadcast(_) => 4,
+            MsgRef::AddPeer(_) => 5,
+            MsgRef::ModifyPeer(_) => 6,
+            MsgRef::RemovePeer(_) => 7,
+            MsgRef::Ack(_) => 8,
+            MsgRef::Unknown(m) => m.kind,
+            MsgRef::Ping(_) => 9,
+            MsgRef::Pong(_) => 10,
+            MsgRef::GetStats(_) => 11,
+            MsgRef::HubStats(_) => 12,
+            MsgRef::ListPeers(_) => 13,
+            MsgRef::PeerList(_) => 14,
+            MsgRef::GetPeer(_) => 15,
+            MsgRef::TxStatus(_) => 16,
+            MsgRef::StopBroadcast(_) => 17,
+            MsgRef::ListBroadcasts(_) => 18,
+            MsgRef::BroadcastList(_) => 19,
+            MsgRef::FlushDeferred(_) => 20,
+            MsgRef::DiscardDeferred(_) => 21,
+            MsgRef::GetDeferred(_) => 22,
+            MsgRef::DeferredStatus(_) => 23,
+            MsgRef::CancelSend(_) => 24,
+            MsgRef::AddToGroup(_) => 25,
+            MsgRef::RemoveFromGroup(_) => 26,
+            MsgRef::SendGroup(_) => 27,
+            MsgRef::GroupStatus(_) => 28,
+            MsgRef::SetRxFilter(_) => 29,
+        }
+    }
+
+    pub fn get_id(&self) -> u32 {
+        match self {
+            MsgRef::Init(m) => m.id,
+            MsgRef::HubConfig(m) => m.id,
+            MsgRef::Send(m) => m.id,
+            MsgRef::Recv(m) => m.id,
+            MsgRef::Broadcast(m) => m.id,
+            MsgRef::AddPeer(m) => m.id,
+            MsgRef::ModifyPeer(m) => m.id,
+            MsgRef::RemovePeer(m) => m.id,
+            MsgRef::Ack(m) => m.id,
+            MsgRef::Unknown(m) => m.id,
+            MsgRef::Ping(m) => m.id,
+            MsgRef::Pong(m) => m.id,
+            MsgRef::GetStats(m) => m.id,
+            MsgRef::HubStats(m) => m.id,
+            MsgRef::ListPeers(m) => m.id,
+            MsgRef::PeerList(m) => m.id,
+            MsgRef::GetPeer(m) => m.id,
+            MsgRef::TxStatus(m) => m.id,
+            MsgRef::StopBroadcast(m) => m.id,
+            MsgRef::ListBroadcasts(m) => m.id,
+            MsgRef::BroadcastList(m) => m.id,
+            MsgRef::FlushDeferred(m) => m.id,
+            MsgRef::DiscardDeferred(m) => m.id,
+            MsgRef::GetDeferred(m) => m.id,
+            MsgRef::DeferredStatus(m) => m.id,
+            MsgRef::CancelSend(m) => m.id,
+            MsgRef::AddToGroup(m) => m.id,
+            MsgRef::RemoveFromGroup(m) => m.id,
+            MsgRef::SendGroup(m) => m.id,
+            MsgRef::GroupStatus(m) => m.id,
+            MsgRef::SetRxFilter(m) => m.id,
+        }
+    }
+
+    /// ESP-NOW payload length (Send/Recv/Broadcast/SendGroup)
+    pub fn data_len(&self) -> Option<usize> {
+        match self {
+            MsgRef::Send(m) => Some(m.data.len()),
+            MsgRef::Recv(m) => Some(m.data.len()),
+            MsgRef::Broadcast(m) => Some(m.data.len()),
+            MsgRef::SendGroup(m) => Some(m.data.len()),
+            _ => None,
+        }
+    }
+
+    /// Semantic validation of the contained type
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        match self {
+            MsgRef::Init(m) => m.validate(),
+            MsgRef::HubConfig(m) => m.validate(),
+            MsgRef::Send(m) => m.validate(),
+            MsgRef::Recv(m) => m.validate(),
+            MsgRef::Broadcast(m) => m.validate(),
+            MsgRef::AddPeer(m) => m.validate_request(),
+            MsgRef::ModifyPeer(m) => m.validate_request(),
+            MsgRef::RemovePeer(m) => m.validate(),
+            MsgRef::Ack(m) => m.validate(),
+            MsgRef::Unknown(m) => m.validate(),
+            MsgRef::Ping(m) => m.validate(),
+            MsgRef::Pong(m) => m.validate(),
+            MsgRef::GetStats(m) => m.validate(),
+            MsgRef::HubStats(m) => m.validate(),
+            MsgRef::ListPeers(m) => m.validate(),
+            MsgRef::PeerList(m) => m.with(PeerList::validate).unwrap_or(Ok(())),
+            MsgRef::GetPeer(m) => m.validate(),
+            MsgRef::TxStatus(m) => m.validate(),
+            MsgRef::StopBroadcast(m) => m.validate(),
+            MsgRef::ListBroadcasts(m) => m.validate(),
+            MsgRef::BroadcastList(m) => m.with(BroadcastList::validate).unwrap_or(Ok(())),
+            MsgRef::FlushDeferred(m) => m.validate(),
+            MsgRef::DiscardDeferred(m) => m.validate(),
+            MsgRef::GetDeferred(m) => m.validate(),
+            MsgRef::DeferredStatus(m) => m.with(DeferredStatus::validate).unwrap_or(Ok(())),
+            MsgRef::CancelSend(m) => m.validate(),
+            MsgRef::AddToGroup(m) => m.validate(),
+            MsgRef::RemoveFromGroup(m) => m.validate(),
+            MsgRef::SendGroup(m) => m.validate(),
+            MsgRef::GroupStatus(m) => m.with(GroupStatus::validate).unwrap_or(Ok(())),
+            MsgRef::SetRxFilter(m) => m.with(RxFilter::validate).unwrap_or(Ok(())),
+        }
+    }
+
+    /// Decode envelope borrowing payload from buf
+    ///
+    /// Payloads larger than MAX_DATA_LEN are rejected with CapacityError
+    pub fn from_slice(buf: &'a [u8]) -> Result<Self, MsgError> {
+        let (kind, body) = decode_envelope(buf)?;
+        let decode = |_| MsgError::PostcardError;
+        let msg = match kind {
+            0 => MsgRef::Init(postcard::from_bytes(body).map_err(decode)?),
+            1 => {
+                let (m, rx_meta) = decode_with_trailer(body)?;
+                MsgRef::HubConfig(HubConfig { rx_meta, ..m })
+            }
+            2 => {
+                let (m, schedule) = decode_with_trailer(body)?;
+                MsgRef::Send(TxDataRef { schedule, ..m })
+            }
+            3 => {
+                let (m, meta) = decode_with_trailer(body)?;
+                MsgRef::Recv(RxDataRef { meta, ..m })
+            }
+            4 => {
+                let (m, replace) = decode_with_trailer(body)?;
+                MsgRef::Broadcast(BroadcastDataRef { replace, ..m })
+            }
+            5 => MsgRef::AddPeer(postcard::from_bytes(body).map_err(decode)?),
+            6 => MsgRef::ModifyPeer(postcard::from_bytes(body).map_err(decode)?),
+            7 => MsgRef::RemovePeer(postcard::from_bytes(body).map_err(decode)?),
+            8 => {
+                let (m, error) = decode_with_trailer(body)?;
+                MsgRef::Ack(Ack { error, ..m })
+            }
+            9 => MsgRef::Ping(postcard::from_bytes(body).map_err(decode)?),
+            10 => MsgRef::Pong(postcard::from_bytes(body).map_err(decode)?),
+            11 => MsgRef::GetStats(postcard::from_bytes(body).map_err(decode)?),
+            12 => MsgRef::HubStats(postcard::from_bytes(body).map_err(decode)?),
+            13 => MsgRef::ListPeers(postcard::from_bytes(body).map_err(decode)?),
+            14 => MsgRef::PeerList(BodyRef::decode(body)?),
+            15 => MsgRef::GetPeer(postcard::from_bytes(body).map_err(decode)?),
+            16 => MsgRef::TxStatus(postcard::from_bytes(body).map_err(decode)?),
+            17 => MsgRef::StopBroadcast(postcard::from_bytes(body).map_err(decode)?),
+            18 => MsgRef::ListBroadcasts(postcard::from_bytes(body).map_err(decode)?),
+            19 => MsgRef::BroadcastList(BodyRef::decode(body)?),
+            20 => MsgRef::FlushDeferred(postcard::from_bytes(body).map_err(decode)?),
+            21 => MsgRef::DiscardDeferred(postcard::from_bytes(body).map_err(decode)?),
+            22 => MsgRef::GetDeferred(postcard::from_bytes(body).map_err(decode)?),
+            23 => MsgRef::DeferredStatus(BodyRef::decode(body)?),
+            24 => MsgRef::CancelSend(postcard::from_bytes(body).map_err(decode)?),
+            25 => MsgRef::AddToGroup(postcard::from_bytes(body).map_err(decode)?),
+            26 => MsgRef::RemoveFromGroup(postcard::from_bytes(body).map_err(decode)?),
+            27 => MsgRef::SendGroup(postcard::from_bytes(body).map_err(decode)?),
+            28 => MsgRef::GroupStatus(BodyRef::decode(body)?),
+            29 => MsgRef::SetRxFilter(BodyRef::decode(body)?),
+            _ => MsgRef::Unknown(UnknownMsgRef {
+                id: postcard::from_bytes(body).map_err(decode)?,
+                kind,
+                bytes: body,
+            }),
+        };
+        match msg.data_len() {
+            Some(len) if len > MAX_DATA_LEN => Err(MsgError::CapacityError),
+            _ => Ok(msg),
+        }
+    }
+
+    /// Decode and validate a received frame (see Msg::from_slice_validated)
+    pub fn from_slice_validated(buf: &'a [u8]) -> Result<Self, InvalidMsg> {
+        let msg = Self::from_slice(buf).map_err(|e| InvalidMsg::decode(buf, e.into()))?;
+        msg.validate().map_err(|e| InvalidMsg {
+            id: Some(msg.get_id()),
+            error: e.into(),
+        })?;
+        Ok(msg)
+    }
+
+    pub fn to_slice<'b>(&self, buf: &'b mut [u8]) -> Result<&'b mut [u8], MsgError> {
+        encode_envelope(self.kind(), buf, |buf| match self {
+            MsgRef::Init(m) => encode_body(m, buf),
+            MsgRef::HubConfig(m) => encode_with_trailer(m, &m.rx_meta, buf),
+            MsgRef::Send(m) => encode_with_trailer(m, &m.schedule, buf),
+            MsgRef::Recv(m) => encode_with_trailer(m, &m.meta, buf),
+            MsgRef::Broadcast(m) => encode_with_trailer(m, &m.replace, buf),
+            MsgRef::AddPeer(m) => encode_body(m, buf),
+            MsgRef::ModifyPeer(m) => encode_body(m, buf),
+            MsgRef::RemovePeer(m) => encode_body(m, buf),
+            MsgRef::Ack(m) => encode_with_trailer(m, &m.error, buf),
+            MsgRef::Unknown(m) => encode_raw(m.bytes, buf),
+            MsgRef::Ping(m) => encode_body(m, buf),
+            MsgRef::Pong(m) => encode_body(m, buf),
+            MsgRef::GetStats(m) => encode_body(m, buf),
+            MsgRef::HubStats(m) => encode_body(m, buf),
+            MsgRef::ListPeers(m) => encode_body(m, buf),
+            MsgRef::PeerList(m) => m.encode(buf),
+            MsgRef::GetPeer(m) => encode_body(m, buf),
+            MsgRef::TxStatus(m) => encode_body(m, buf),
+            MsgRef::StopBroadcast(m) => encode_body(m, buf),
+            MsgRef::ListBroadcasts(m) => encode_body(m, buf),
+            MsgRef::BroadcastList(m) => m.encode(buf),
+            MsgRef::FlushDeferred(m) => encode_body(m, buf),
+            MsgRef::DiscardDeferred(m) => encode_body(m, buf),
+            MsgRef::GetDeferred(m) => encode_body(m, buf),
+            MsgRef::DeferredStatus(m) => m.encode(buf),
+            MsgRef::CancelSend(m) => encode_body(m, buf),
+            MsgRef::AddToGroup(m) => encode_body(m, buf),
+            MsgRef::RemoveFromGroup(m) => encode_body(m, buf),
+            MsgRef::SendGroup(m) => encode_body(m, buf),
+            MsgRef::GroupStatus(m) => m.encode(buf),
+            MsgRef::SetRxFilter(m) => m.encode(buf),
+        })
+    }
+
+    /// Copy into owned Msg
+    pub fn to_msg(&self) -> Result<Msg, MsgError> {
+        Msg::try_from(self)
+    }
+}
+
+impl Serialize for MsgRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let encoded = self.to_slice(&mut buf).map_err(ser::Error::custom)?;
+        serializer.serialize_bytes(encoded)
+    }
+}
+
+impl<'a> From<&'a Msg> for MsgRef<'a> {
+    fn from(msg: &'a Msg) -> Self {
+        match msg {
+            Msg::Init(m) => MsgRef::Init(m.clone()),
+            Msg::HubConfig(m) => MsgRef::HubConfig(m.clone()),
+            Msg::Send(m) => MsgRef::Send(m.into()),
+            Msg::Recv(m) => MsgRef::Recv(m.into()),
+            Msg::Broadcast(m) => MsgRef::Broadcast(m.into()),
+            Msg::AddPeer(m) => MsgRef::AddPeer(m.clone()),
+            Msg::ModifyPeer(m) => MsgRef::ModifyPeer(m.clone()),
+            Msg::RemovePeer(m) => MsgRef::RemovePeer(m.clone()),
+            Msg::Ack(m) => MsgRef::Ack(m.clone()),
+            Msg::Unknown(m) => MsgRef::Unknown(m.into()),
+            Msg::Ping(m) => MsgRef::Ping(m.clone()),
+            Msg::Pong(m) => MsgRef::Pong(m.clone()),
+            Msg::GetStats(m) => MsgRef::GetStats(m.clone()),
+            Msg::HubStats(m) => MsgRef::HubStats(m.clone()),
+            Msg::ListPeers(m) => MsgRef::ListPeers(m.clone()),
+            Msg::PeerList(m) => MsgRef::PeerList(BodyRef::new(m.id, m)),
+            Msg::GetPeer(m) => MsgRef::GetPeer(m.clone()),
+            Msg::TxStatus(m) => MsgRef::TxStatus(m.clone()),
+            Msg::StopBroadcast(m) => MsgRef::StopBroadcast(m.clone()),
+            Msg::ListBroadcasts(m) => MsgRef::ListBroadcasts(m.clone()),
+            Msg::BroadcastList(m) => MsgRef::BroadcastList(BodyRef::new(m.id, m)),
+            Msg::FlushDeferred(m) => MsgRef::FlushDeferred(m.clone()),
+            Msg::DiscardDeferred(m) => MsgRef::DiscardDeferred(m.clone()),
+            Msg::GetDeferred(m) => MsgRef::GetDeferred(m.clone()),
+            Msg::DeferredStatus(m) => MsgRef::DeferredStatus(BodyRef::new(m.id, m)),
+            Msg::CancelSend(m) => MsgRef::CancelSend(m.clone()),
+            Msg::AddToGroup(m) => MsgRef::AddToGroup(m.clone()),
+            Msg::RemoveFromGroup(m) => MsgRef::RemoveFromGroup(m.clone()),
+            Msg::SendGroup(m) => MsgRef::SendGroup(m.into()),
+            Msg::GroupStatus(m) => MsgRef::GroupStatus(BodyRef::new(m.id, m)),
+            Msg::SetRxFilter(m) => MsgRef::SetRxFilter(BodyRef::new(m.id, m)),
+        }
+    }
+}
+
+impl TryFrom<&MsgRef<'_>> for Msg {
+    type Error = MsgError;
+
+    fn try_from(msg: &MsgRef<'_>) -> Result<Self, MsgError> {
+        let copy =
+            |data: &[u8]| heapless::Vec::from_slice(data).map_err(|_| MsgError::CapacityError);
+        Ok(match msg {
+            MsgRef::Init(m) => Msg::Init(m.clone()),
+            MsgRef::HubConfig(m) => Msg::HubConfig(m.clone()),
+            MsgRef::Send(m) => Msg::Send(TxData {
+                id: m.id,
+                dst_addr: m.dst_addr,
+                data: copy(m.data)?,
+                defer: m.defer,
+                schedule: m.schedule,
+            }),
+            MsgRef::Recv(m) => Msg::Recv(RxData {
+                id: m.id,
+                src_addr: m.src_addr,
+                dst_addr: m.dst_addr,
+                data: copy(m.data)?,
+                rssi: m.rssi,
+                meta: m.meta,
+            }),
+            MsgRef::Broadcast(m) => Msg::Broadcast(BroadcastData {
+                id: m.id,
+                data: copy(m.data)?,
+                interval: m.interval,
+                replace: m.replace,
+            }),
+            MsgRef::AddPeer(m) => Msg::AddPeer(m.clone()),
+            MsgRef::ModifyPeer(m) => Msg::ModifyPeer(m.clone()),
+            MsgRef::RemovePeer(m) => Msg::RemovePeer(m.clone()),
+            MsgRef::Ack(m) => Msg::Ack(m.clone()),
+            MsgRef::Unknown(m) => Msg::Unknown(UnknownMsg {
+                id: m.id,
+                kind: m.kind,
+                bytes: heapless::Vec::from_slice(m.bytes).map_err(|_| MsgError::CapacityError)?,
+            }),
+            MsgRef::Ping(m) => Msg::Ping(m.clone()),
+            MsgRef::Pong(m) => Msg::Pong(m.clone()),
+            MsgRef::GetStats(m) => Msg::GetStats(m.clone()),
+            MsgRef::HubStats(m) => Msg::HubStats(m.clone()),
+            MsgRef::ListPeers(m) => Msg::ListPeers(m.clone()),
+            MsgRef::PeerList(m) => Msg::PeerList(m.get()?),
+            MsgRef::GetPeer(m) => Msg::GetPeer(m.clone()),
+            MsgRef::TxStatus(m) => Msg::TxStatus(m.clone()),
+            MsgRef::StopBroadcast(m) => Msg::StopBroadcast(m.clone()),
+            MsgRef::ListBroadcasts(m) => Msg::ListBroadcasts(m.clone()),
+            MsgRef::BroadcastList(m) => Msg::BroadcastList(m.get()?),
+            MsgRef::FlushDeferred(m) => Msg::FlushDeferred(m.clone()),
+            MsgRef::DiscardDeferred(m) => Msg::DiscardDeferred(m.clone()),
+            MsgRef::GetDeferred(m) => Msg::GetDeferred(m.clone()),
+            MsgRef::DeferredStatus(m) => Msg::DeferredStatus(m.get()?),
+            MsgRef::CancelSend(m) => Msg::CancelSend(m.clone()),
+            MsgRef::AddToGroup(m) => Msg::AddToGroup(m.clone()),
+            MsgRef::RemoveFromGroup(m) => Msg::RemoveFromGroup(m.clone()),
+            MsgRef::SendGroup(m) => Msg::SendGroup(SendGroup {
+                id: m.id,
+                group: m.group,
+                data: copy(m.data)?,
+                defer: m.defer,
+            }),
+            MsgRef::GroupStatus(m) => Msg::GroupStatus(m.get()?),
+            MsgRef::SetRxFilter(m) => Msg::SetRxFilter(m.get()?),
+        })
+    }
+}
+
+impl TryFrom<MsgRef<'_>> for Msg {
+    type Error = MsgError;
+
+    fn try_from(msg: MsgRef<'_>) -> Result<Self, MsgError> {
+        Msg::try_from(&msg)
+    }
+}
+
+impl<'a> From<&'a TxData> for TxDataRef<'a> {
+    fn from(m: &'a TxData) -> Self {
+        Self {
+            id: m.id,
+            dst_addr: m.dst_addr,
+            data: &m.data,
+            defer: m.defer,
+            schedule: m.schedule,
+        }
+    }
+}
+
+impl<'a> From<&'a RxData> for RxDataRef<'a> {
+    fn from(m: &'a RxData) -> Self {
+        Self {
+            id: m.id,
+            src_addr: m.src_addr,
+            dst_addr: m.dst_addr,
+            data: &m.data,
+            rssi: m.rssi,
+            meta: m.meta,
+        }
+    }
+}
+
+impl<'a> From<&'a BroadcastData> for BroadcastDataRef<'a> {
+    fn from(m: &'a BroadcastData) -> Self {
+        Self {
+            id: m.id,
+            data: &m.data,
+            interval: m.interval,
+            replace: m.replace,
+        }
+    }
+}
+
+impl<'a> From<&'a SendGroup> for SendGroupRef<'a> {
+    fn from(m: &'a SendGroup) -> Self {
+        Self {
+            id: m.id,
+            group: m.group,
+            data: &m.data,
+            defer: m.defer,
+        }
+    }
+}
+
+impl<'a> From<&'a UnknownMsg> for UnknownMsgRef<'a> {
+    fn from(m: &'a UnknownMsg) -> Self {
+        Self {
+            id: m.id,
+            kind: m.kind,
+            bytes: &m.bytes,
+        }
+    }
+}
+
+impl TxDataRef<'_> {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        check_data(self.data)
+    }
+}
+
+impl Display for TxDataRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] TxData: dst={} defer={} data=\"{}\"",
+            self.id,
+            format_mac(&self.dst_addr),
+            self.defer,
+            display_slice::<64>(self.data)
+        )?;
+        match &self.schedule {
+            Some(schedule) => write!(f, " {}", schedule),
+            None => Ok(()),
+        }
+    }
+}
+
+impl defmt::Format for TxDataRef<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] TxData: dst={} defer={} data=\"{}\"",
+            self.id,
+            format_mac(&self.dst_addr),
+            self.defer,
+            display_slice::<64>(self.data)
+        );
+        if let Some(schedule) = &self.schedule {
+            defmt::write!(fmt, " {}", schedule)
+        }
+    }
+}
+
+impl RxDataRef<'_> {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.meta.as_ref().map_or(Ok(()), RxMeta::validate)
+    }
+}
+
+impl Display for RxDataRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] RxData: src={} dst={} rssi={} data=\"{}\"",
+            self.id,
+            format_mac(&self.src_addr),
+            format_mac(&self.dst_addr),
+            self.rssi,
+            display_slice::<64>(self.data)
+        )?;
+        match &self.meta {
+            Some(meta) => write!(f, " {}", meta),
+            None => Ok(()),
+        }
+    }
+}
+
+impl defmt::Format for RxDataRef<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] RxData: src={} dst={} rssi={} data=\"{}\"",
+            self.id,
+            format_mac(&self.src_addr),
+            format_mac(&self.dst_addr),
+            self.rssi,
+            display_slice::<64>(self.data)
+        );
+        if let Some(meta) = &self.meta {
+            defmt::write!(fmt, " {}", meta)
+        }
+    }
+}
+
+impl BroadcastDataRef<'_> {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        check_data(self.data)?;
+        check_interval(self.interval)
+    }
+}
+
+impl Display for BroadcastDataRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] BroadcastData: interval={:?} replace={:?} data=\"{}\"",
+            self.id,
+            self.interval,
+            self.replace,
+            display_slice::<64>(self.data)
+        )
+    }
+}
+
+impl defmt::Format for BroadcastDataRef<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] BroadcastData: interval={:?} replace={:?} data=\"{}\"",
+            self.id,
+            self.interval,
+            self.replace,
+            display_slice::<64>(self.data)
+        )
+    }
+}
+
+impl SendGroupRef<'_> {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        check_data(self.data)
+    }
+}
+
+impl Display for SendGroupRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] SendGroup: group={} defer={} data=\"{}\"",
+            self.id,
+            self.group,
+            self.defer,
+            display_slice::<64>(self.data)
+        )
+    }
+}
+
+impl defmt::Format for SendGroupRef<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] SendGroup: group={} defer={} data=\"{}\"",
+            self.id,
+            self.group,
+            self.defer,
+            display_slice::<64>(self.data)
+        )
+    }
+}
+
+impl UnknownMsgRef<'_> {
+    /// Kind must be one this version does not decode and bytes must start with id
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.kind <= MAX_KIND {
+            return Err(ValidationError::KnownKind(self.kind));
+        }
+        match postcard::from_bytes::<u32>(self.bytes) {
+            Ok(id) if id == self.id => Ok(()),
+            _ => Err(ValidationError::UnknownId),
+        }
+    }
+}
+
+impl Display for UnknownMsgRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] Unknown: kind={} bytes=\"{}\"",
+            self.id,
+            self.kind,
+            display_slice::<64>(self.bytes)
+        )
+    }
+}
+
+impl defmt::Format for UnknownMsgRef<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] Unknown: kind={} bytes=\"{}\"",
+            self.id,
+            self.kind,
+            display_slice::<64>(self.bytes)
+        )
+    }
+}
+
+impl<'a, T: Deserialize<'a> + Display> Display for BodyRef<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.with(|m| write!(f, "{}", m)) {
+            Ok(r) => r,
+            Err(e) => write!(f, "[{}] {}", self.id, e),
+        }
+    }
+}
+
+impl<'a, T: Deserialize<'a> + defmt::Format> defmt::Format for BodyRef<'a, T> {
+    fn format(&self, fmt: defmt::Formatter) {
+        if let Err(e) = self.with(|m| defmt::write!(fmt, "{}", m)) {
+            defmt::write!(fmt, "[{}] {}", self.id, e)
+        }
+    }
+}
+
+impl Display for MsgRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MsgRef::Init(m) => write!(f, "{}", m),
+            MsgRef::HubConfig(m) => write!(f, "{}", m),
+            MsgRef::Send(m) => write!(f, "{}", m),
+            MsgRef::Recv(m) => write!(f, "{}", m),
+            MsgRef::Broadcast(m) => write!(f, "{}", m),
+            MsgRef::AddPeer(m) => write!(f, "{}", m),
+            MsgRef::ModifyPeer(m) => write!(f, "{}", m),
+            MsgRef::RemovePeer(m) => write!(f, "{}", m),
+            MsgRef::Ack(m) => write!(f, "{}", m),
+            MsgRef::Unknown(m) => write!(f, "{}", m),
+            MsgRef::Ping(m) => write!(f, "{}", m),
+            MsgRef::Pong(m) => write!(f, "{}", m),
+            MsgRef::GetStats(m) => write!(f, "{}", m),
+            MsgRef::HubStats(m) => write!(f, "{}", m),
+            MsgRef::ListPeers(m) => write!(f, "{}", m),
+            MsgRef::PeerList(m) => write!(f, "{}", m),
+            MsgRef::GetPeer(m) => write!(f, "{}", m),
+            MsgRef::TxStatus(m) => write!(f, "{}", m),
+            MsgRef::StopBroadcast(m) => write!(f, "{}", m),
+            MsgRef::ListBroadcasts(m) => write!(f, "{}", m),
+            MsgRef::BroadcastList(m) => write!(f, "{}", m),
+            MsgRef::FlushDeferred(m) => write!(f, "{}", m),
+            MsgRef::DiscardDeferred(m) => write!(f, "{}", m),
+            MsgRef::GetDeferred(m) => write!(f, "{}", m),
+            MsgRef::DeferredStatus(m) => write!(f, "{}", m),
+            MsgRef::CancelSend(m) => write!(f, "{}", m),
+            MsgRef::AddToGroup(m) => write!(f, "{}", m),
+            MsgRef::RemoveFromGroup(m) => write!(f, "{}", m),
+            MsgRef::SendGroup(m) => write!(f, "{}", m),
+            MsgRef::GroupStatus(m) => write!(f, "{}", m),
+            MsgRef::SetRxFilter(m) => write!(f, "{}", m),
+        }
+    }
+}
+
+impl defmt::Format for MsgRef<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        match self {
+            MsgRef::Init(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::HubConfig(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::Send(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::Recv(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::Broadcast(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::AddPeer(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::ModifyPeer(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::RemovePeer(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::Ack(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::Unknown(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::Ping(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::Pong(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::GetStats(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::HubStats(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::ListPeers(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::PeerList(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::GetPeer(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::TxStatus(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::StopBroadcast(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::ListBroadcasts(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::BroadcastList(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::FlushDeferred(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::DiscardDeferred(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::GetDeferred(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::DeferredStatus(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::CancelSend(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::AddToGroup(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::RemoveFromGroup(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::SendGroup(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::GroupStatus(m) => defmt::write!(fmt, "{}", m),
+            MsgRef::SetRxFilter(m) => defmt::write!(fmt, "{}", m),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msgs() -> [Msg; 5] {
+        [
+            Msg::Send(TxData {
+                id: 1,
+                dst_addr: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                data: heapless::Vec::from_slice(&[0xaa; MAX_DATA_LEN]).unwrap(),
+                defer: true,
+                schedule: None,
+            }),
+            Msg::Recv(RxData {
+                id: 2,
+                src_addr: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                dst_addr: [0xff; 6],
+                data: heapless::Vec::from_slice(b"hello").unwrap(),
+                rssi: -60,
+                meta: None,
+            }),
+            Msg::Broadcast(BroadcastData {
+                id: 3,
+                data: heapless::Vec::from_slice(b"beacon").unwrap(),
+                interval: Some(1000),
+                replace: None,
+            }),
+            Msg::SendGroup(SendGroup {
+                id: 4,
+                group: 2,
+                data: heapless::Vec::from_slice(b"lights off").unwrap(),
+                defer: false,
+            }),
+            Msg::Unknown(UnknownMsg {
+                id: 1234,
+                kind: 200,
+                bytes: heapless::Vec::from_slice(&[0xd2, 0x09, 0x01, 0x02]).unwrap(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_decode_borrowed() {
+        for m in msgs() {
+            let mut buf = [0_u8; MAX_MSG_LEN];
+            let encoded = m.to_slice(&mut buf).unwrap();
+            let r = MsgRef::from_slice(encoded).unwrap();
+            assert_eq!(r, MsgRef::from(&m));
+            assert_eq!(r.get_id(), m.get_id());
+            assert_eq!(r.to_msg(), Ok(m));
+        }
+    }
+
+    #[test]
+    fn test_payload_borrowed() {
+        let m = &msgs()[1];
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let encoded = m.to_slice(&mut buf).unwrap();
+        let range = encoded.as_ptr_range();
+        match MsgRef::from_slice(encoded).unwrap() {
+            MsgRef::Recv(rx) => assert!(range.contains(&rx.data.as_ptr())),
+            r => panic!("{}", r),
+        }
+    }
+
+    #[test]
+    fn test_encode_identical() {
+        for m in msgs() {
+            let mut owned = [0_u8; MAX_MSG_LEN];
+            let mut borrowed = [0_u8; MAX_MSG_LEN];
+            assert_eq!(
+                MsgRef::from(&m).to_slice(&mut borrowed).unwrap(),
+                m.to_slice(&mut owned).unwrap()
+            );
+            assert_eq!(
+                postcard::to_vec::<_, MAX_MSG_LEN>(&MsgRef::from(&m)).unwrap(),
+                postcard::to_vec::<_, MAX_MSG_LEN>(&m).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_oversize_payload() {
+        let data = [0_u8; MAX_DATA_LEN + 1];
+        let m = MsgRef::Send(TxDataRef {
+            id: 1,
+            dst_addr: [0xff; 6],
+            data: &data,
+            defer: false,
+            schedule: None,
+        });
+        let mut buf = [0_u8; MAX_MSG_LEN + 8];
+        let encoded = m.to_slice(&mut buf).unwrap();
+        assert_eq!(MsgRef::from_slice(encoded), Err(MsgError::CapacityError));
+        assert_eq!(m.to_msg(), Err(MsgError::CapacityError));
+    }
+
+    #[test]
+    fn test_body_ref() {
+        let peers = [1, 2, 3].map(|n| PeerInfo {
+            id: n,
+            peer_address: [0x02, 0, 0, 0, 0, n as u8],
+            lmk: None,
+            channel: Some(6),
+            encrypt: false,
+        });
+        let m = Msg::PeerList(PeerList::page(7, 3, 1, &peers));
+        let mut buf = [0_u8; MAX_MSG_LEN];
+        let encoded = m.to_slice(&mut buf).unwrap();
+        let r = MsgRef::from_slice(encoded).unwrap();
+        assert_eq!(r, MsgRef::from(&m));
+        assert_eq!(r.get_id(), 7);
+        assert_eq!(r.to_string(), m.to_string());
+        assert_eq!(r.to_msg(), Ok(m.clone()));
+        let mut reencoded = [0_u8; MAX_MSG_LEN];
+        assert_eq!(
+            r.to_slice(&mut reencoded).unwrap(),
+            m.to_slice(&mut buf).unwrap()
+        );
+        // Table messages are not copied into MsgRef
+        assert!(core::mem::size_of::<MsgRef>() < core::mem::size_of::<PeerList>() / 4);
+    }
+}
@@ -4,6 +4,7 @@ use rquickjs::{class::Trace, ArrayBuffer, Ctx, JsLifetime};
 #[cfg(feature = "js")]
 use crate::util::buf_to_array;
 
+use crate::types::validate::{ValidationError, MAX_CHANNEL};
 use crate::util::format_mac;
 
 use core::fmt::Display;
@@ -21,6 +22,36 @@ pub struct PeerInfo {
     pub encrypt: bool,
 }
 
+impl PeerInfo {
+    /// Copy with LMK removed (for reporting peer table)
+    pub fn redacted(&self) -> Self {
+        Self {
+            lmk: None,
+            ..self.clone()
+        }
+    }
+
+    /// Checks that apply to every PeerInfo (including redacted PeerList entries)
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        match self.channel {
+            Some(c) if c > MAX_CHANNEL => Err(ValidationError::InvalidChannel(c)),
+            _ if self.encrypt && self.peer_address == [0xff; 6] => {
+                Err(ValidationError::EncryptedBroadcast)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// AddPeer/ModifyPeer request - an encrypted peer must also carry the LMK
+    pub fn validate_request(&self) -> Result<(), ValidationError> {
+        self.validate()?;
+        match self.encrypt && self.lmk.is_none() {
+            true => Err(ValidationError::MissingLmk),
+            false => Ok(()),
+        }
+    }
+}
+
 #[cfg(feature = "js")]
 #[rquickjs::methods]
 impl PeerInfo {
@@ -37,13 +68,15 @@ impl PeerInfo {
             Some(buf) => Some(buf_to_array::<16>(&ctx, &buf, "Invalid LMK")?),
             None => None,
         };
-        Ok(Self {
+        let m = Self {
             id,
             peer_address: buf_to_array::<6>(&ctx, &peer_address, "Invalid peer_addr")?,
             lmk,
             channel,
             encrypt,
-        })
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
     }
     pub fn debug(&self) -> String {
         format!("PeerInfo: {:?}", self)
@@ -86,6 +119,12 @@ pub struct PeerAddress {
     pub address: [u8; 6],
 }
 
+impl PeerAddress {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
 #[cfg(feature = "js")]
 #[rquickjs::methods]
 impl PeerAddress {
@@ -0,0 +1,286 @@
+#[cfg(feature = "js")]
+use rquickjs::{class::Trace, Ctx, Exception, JsLifetime};
+
+use crate::types::validate::ValidationError;
+use crate::types::PeerInfo;
+use crate::util::format_mac;
+
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+/// Maximum PeerInfo entries per PeerList page (ESP-NOW supports 20 peers)
+pub const PEER_LIST_LEN: usize = 10;
+
+// Server -> Hub :: List peer table from offset
+//
+// The hub replies with a PeerList page - the server requests the next page
+// (PeerList::next_offset) until all peers have been received.
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct ListPeers {
+    pub id: u32,
+    pub offset: u8,
+}
+
+impl ListPeers {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl ListPeers {
+    #[qjs(constructor)]
+    pub fn new(id: u32, offset: u8) -> Self {
+        Self { id, offset }
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "offset")]
+    pub fn get_offset(&self) -> u8 {
+        self.offset
+    }
+    pub fn debug(&self) -> String {
+        format!("ListPeers: {:?}", self)
+    }
+}
+
+impl Display for ListPeers {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[{}] ListPeers: offset={}", self.id, self.offset)
+    }
+}
+
+impl defmt::Format for ListPeers {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "[{}] ListPeers: offset={}", self.id, self.offset)
+    }
+}
+
+// Hub -> Server :: Page of peer table (reply to ListPeers or GetPeer)
+//
+// Entries are PeerInfo with lmk removed and id set to the peer index in the
+// hub table. GetPeer replies with a single entry (or Ack PeerNotFound).
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct PeerList {
+    pub id: u32,
+    pub rx_id: u32,
+    pub offset: u8,
+    pub total: u8,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub peers: heapless::Vec<PeerInfo, PEER_LIST_LEN>,
+}
+
+impl PeerList {
+    /// Build page starting at offset from hub peer table (offset past the
+    /// end is clamped to total - an empty last page)
+    pub fn page(id: u32, rx_id: u32, offset: u8, peers: &[PeerInfo]) -> Self {
+        let total = peers.len().min(u8::MAX as usize);
+        let offset = offset.min(total as u8);
+        Self {
+            id,
+            rx_id,
+            offset,
+            total: total as u8,
+            peers: peers[offset as usize..total]
+                .iter()
+                .zip(offset..)
+                .take(PEER_LIST_LEN)
+                .map(|(p, i)| PeerInfo {
+                    id: i as u32,
+                    ..p.redacted()
+                })
+                .collect(),
+        }
+    }
+
+    /// Reply to GetPeer - single entry for address (None if not found)
+    pub fn find(id: u32, rx_id: u32, address: &[u8; 6], peers: &[PeerInfo]) -> Option<Self> {
+        let index = peers.iter().position(|p| &p.peer_address == address)?;
+        let mut list = Self::page(id, rx_id, index as u8, peers);
+        list.peers.truncate(1);
+        Some(list)
+    }
+
+    /// Offset of next page (None if this is the last page)
+    pub fn next_offset(&self) -> Option<u8> {
+        let next = self.offset as usize + self.peers.len();
+        if !self.peers.is_empty() && next < self.total as usize {
+            Some(next as u8)
+        } else {
+            None
+        }
+    }
+
+    /// Peers are redacted (no LMK) so the LMK rule does not apply to them
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.offset as usize + self.peers.len() > self.total as usize {
+            return Err(ValidationError::PeerListRange);
+        }
+        self.peers.iter().try_for_each(PeerInfo::validate)
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl PeerList {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        id: u32,
+        rx_id: u32,
+        offset: u8,
+        total: u8,
+        peers: Vec<PeerInfo>,
+    ) -> rquickjs::Result<Self> {
+        let m = Self {
+            id,
+            rx_id,
+            offset,
+            total,
+            peers: heapless::Vec::from_slice(&peers)
+                .map_err(|_| Exception::throw_message(&ctx, "Too many peers"))?,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "rx_id")]
+    pub fn get_rx_id(&self) -> u32 {
+        self.rx_id
+    }
+    #[qjs(get, rename = "offset")]
+    pub fn get_offset(&self) -> u8 {
+        self.offset
+    }
+    #[qjs(get, rename = "total")]
+    pub fn get_total(&self) -> u8 {
+        self.total
+    }
+    #[qjs(get, rename = "peers")]
+    pub fn get_peers(&self) -> Vec<PeerInfo> {
+        self.peers.to_vec()
+    }
+    #[qjs(get, rename = "next_offset")]
+    pub fn get_next_offset(&self) -> Option<u8> {
+        self.next_offset()
+    }
+    pub fn debug(&self) -> String {
+        format!("PeerList: {:?}", self)
+    }
+}
+
+impl Display for PeerList {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] PeerList: rx_id={} offset={} total={} peers=[",
+            self.id, self.rx_id, self.offset, self.total
+        )?;
+        for (i, p) in self.peers.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", format_mac(&p.peer_address))?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl defmt::Format for PeerList {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] PeerList: rx_id={} offset={} total={} peers=[",
+            self.id,
+            self.rx_id,
+            self.offset,
+            self.total
+        );
+        for (i, p) in self.peers.iter().enumerate() {
+            if i > 0 {
+                defmt::write!(fmt, ",");
+            }
+            defmt::write!(fmt, "{}", format_mac(&p.peer_address));
+        }
+        defmt::write!(fmt, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peers(n: u8) -> Vec<PeerInfo> {
+        (0..n)
+            .map(|i| PeerInfo {
+                id: 100,
+                peer_address: [0x01, 0x02, 0x03, 0x04, 0x05, i],
+                lmk: Some([i; 16]),
+                channel: Some(1),
+                encrypt: true,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_page() {
+        let all = peers(15);
+        let first = PeerList::page(1, 2, 0, &all);
+        assert_eq!((first.total, first.peers.len()), (15, PEER_LIST_LEN));
+        assert_eq!(first.next_offset(), Some(PEER_LIST_LEN as u8));
+        assert!(first.peers.iter().all(|p| p.lmk.is_none() && p.encrypt));
+        assert_eq!(first.peers[3].id, 3);
+
+        let second = PeerList::page(3, 4, first.next_offset().unwrap(), &all);
+        assert_eq!(second.peers.len(), 5);
+        assert_eq!(second.peers[0].peer_address, all[10].peer_address);
+        assert_eq!(second.peers[0].id, 10);
+        assert_eq!(second.next_offset(), None);
+    }
+
+    #[test]
+    fn test_page_empty() {
+        let list = PeerList::page(1, 2, 0, &[]);
+        assert!(list.peers.is_empty());
+        assert_eq!(list.next_offset(), None);
+        assert_eq!(list.validate(), Ok(()));
+        let list = PeerList::page(1, 2, 50, &peers(5));
+        assert!(list.peers.is_empty());
+        assert_eq!((list.offset, list.total), (5, 5));
+        assert_eq!(list.next_offset(), None);
+        assert_eq!(list.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_find() {
+        let all = peers(5);
+        let list = PeerList::find(1, 2, &all[3].peer_address, &all).unwrap();
+        assert_eq!(list.peers.len(), 1);
+        assert_eq!(
+            list.peers[0],
+            PeerInfo {
+                id: 3,
+                ..all[3].redacted()
+            }
+        );
+        assert_eq!(PeerList::find(1, 2, &[0xff; 6], &all), None);
+    }
+
+    #[test]
+    fn test_page_fits_frame() {
+        let list = PeerList::page(u32::MAX, u32::MAX, 0, &peers(20));
+        let msg = crate::Msg::PeerList(list);
+        let mut buf = [0_u8; crate::MAX_MSG_LEN];
+        assert!(msg.to_slice(&mut buf).is_ok());
+    }
+}
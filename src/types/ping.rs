@@ -0,0 +1,143 @@
+#[cfg(feature = "js")]
+use rquickjs::{class::Trace, JsLifetime};
+
+use crate::types::validate::ValidationError;
+
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+// Bidirectional :: Heartbeat
+//
+// Either side can send Ping - the receiver replies immediately with Pong
+// echoing the ping id and timestamp. Timestamps are in ms from the sender's
+// own clock (no synchronisation is assumed).
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct Ping {
+    pub id: u32,
+    pub timestamp: u64,
+}
+
+impl Ping {
+    /// Reply to ping (timestamp is the responder's clock)
+    pub fn pong(&self, id: u32, timestamp: u64) -> Pong {
+        Pong {
+            id,
+            ping_id: self.id,
+            ping_timestamp: self.timestamp,
+            timestamp,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl Ping {
+    #[qjs(constructor)]
+    pub fn new(id: u32, timestamp: u64) -> Self {
+        Self { id, timestamp }
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "timestamp")]
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    #[qjs(rename = "pong")]
+    pub fn pong_js(&self, id: u32, timestamp: u64) -> Pong {
+        self.pong(id, timestamp)
+    }
+    pub fn debug(&self) -> String {
+        format!("Ping: {:?}", self)
+    }
+}
+
+impl Display for Ping {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[{}] Ping: timestamp={}", self.id, self.timestamp)
+    }
+}
+
+impl defmt::Format for Ping {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "[{}] Ping: timestamp={}", self.id, self.timestamp)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct Pong {
+    pub id: u32,
+    pub ping_id: u32,
+    pub ping_timestamp: u64,
+    pub timestamp: u64,
+}
+
+impl Pong {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl Pong {
+    #[qjs(constructor)]
+    pub fn new(id: u32, ping_id: u32, ping_timestamp: u64, timestamp: u64) -> Self {
+        Self {
+            id,
+            ping_id,
+            ping_timestamp,
+            timestamp,
+        }
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "ping_id")]
+    pub fn get_ping_id(&self) -> u32 {
+        self.ping_id
+    }
+    #[qjs(get, rename = "ping_timestamp")]
+    pub fn get_ping_timestamp(&self) -> u64 {
+        self.ping_timestamp
+    }
+    #[qjs(get, rename = "timestamp")]
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    pub fn debug(&self) -> String {
+        format!("Pong: {:?}", self)
+    }
+}
+
+impl Display for Pong {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] Pong: ping_id={} ping_timestamp={} timestamp={}",
+            self.id, self.ping_id, self.ping_timestamp, self.timestamp
+        )
+    }
+}
+
+impl defmt::Format for Pong {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] Pong: ping_id={} ping_timestamp={} timestamp={}",
+            self.id,
+            self.ping_id,
+            self.ping_timestamp,
+            self.timestamp
+        )
+    }
+}
@@ -4,13 +4,19 @@ use rquickjs::{class::Trace, ArrayBuffer, Ctx, Exception, JsLifetime};
 #[cfg(feature = "js")]
 use crate::util::buf_to_array;
 
-use crate::util::{display_vec, format_mac};
+use crate::types::validate::{check_channel, ValidationError};
+use crate::types::RxDataRef;
 use crate::MAX_DATA_LEN;
 
 use core::fmt::Display;
 use serde::{Deserialize, Serialize};
 
 // Hub -> Server :: RX esp-now msg
+//
+// meta is only sent when enabled in HubConfig (rx_meta) - it is encoded as
+// an optional trailer after the RxData body so older decoders ignore it and
+// RxData from older hubs decodes with meta = None. It is not included in the
+// serde (Monitor) encoding.
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
@@ -23,6 +29,15 @@ pub struct RxData {
     #[cfg_attr(feature = "js", qjs(skip_trace))]
     pub data: heapless::Vec<u8, MAX_DATA_LEN>,
     pub rssi: i32,
+    #[serde(skip)]
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub meta: Option<RxMeta>,
+}
+
+impl RxData {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        RxDataRef::from(self).validate()
+    }
 }
 
 #[cfg(feature = "js")]
@@ -36,15 +51,19 @@ impl RxData {
         dst_addr: ArrayBuffer<'_>,
         data: ArrayBuffer<'_>,
         rssi: i32,
+        meta: Option<RxMeta>,
     ) -> rquickjs::Result<Self> {
-        Ok(Self {
+        let m = Self {
             id,
             src_addr: buf_to_array::<6>(&ctx, &src_addr, "Invalid src_addr")?,
             dst_addr: buf_to_array::<6>(&ctx, &dst_addr, "Invalid dst_addr")?,
             data: heapless::Vec::from_slice(data.as_bytes().unwrap_or(&[]))
                 .map_err(|_| Exception::throw_message(&ctx, "data invalid"))?,
             rssi,
-        })
+            meta,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
     }
     #[qjs(get, rename = "id")]
     pub fn get_id(&self) -> u32 {
@@ -66,35 +85,115 @@ impl RxData {
     pub fn get_rssi(&self) -> i32 {
         self.rssi
     }
+    #[qjs(get, rename = "meta")]
+    pub fn get_meta(&self) -> Option<RxMeta> {
+        self.meta
+    }
     pub fn debug(&self) -> String {
         format!("RxData: {:?}", self)
     }
 }
 
 impl Display for RxData {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", RxDataRef::from(self))
+    }
+}
+
+impl defmt::Format for RxData {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{}", RxDataRef::from(self))
+    }
+}
+
+/// Extended receive info (from esp_now_recv_info_t rx_ctrl)
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct RxMeta {
+    pub channel: u8,
+    /// Noise floor (dBm)
+    pub noise_floor: i8,
+    /// PHY rate (rx_ctrl.rate)
+    pub rate: u8,
+    /// 0: non-HT (11bg) 1: HT (11n) 3: VHT (11ac)
+    pub sig_mode: u8,
+    /// Radio timestamp (us)
+    pub timestamp: u32,
+}
+
+impl RxMeta {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        check_channel(self.channel)
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl RxMeta {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        channel: u8,
+        noise_floor: i8,
+        rate: u8,
+        sig_mode: u8,
+        timestamp: u32,
+    ) -> rquickjs::Result<Self> {
+        let m = Self {
+            channel,
+            noise_floor,
+            rate,
+            sig_mode,
+            timestamp,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
+    }
+    #[qjs(get, rename = "channel")]
+    pub fn get_channel(&self) -> u8 {
+        self.channel
+    }
+    #[qjs(get, rename = "noise_floor")]
+    pub fn get_noise_floor(&self) -> i8 {
+        self.noise_floor
+    }
+    #[qjs(get, rename = "rate")]
+    pub fn get_rate(&self) -> u8 {
+        self.rate
+    }
+    #[qjs(get, rename = "sig_mode")]
+    pub fn get_sig_mode(&self) -> u8 {
+        self.sig_mode
+    }
+    #[qjs(get, rename = "timestamp")]
+    pub fn get_timestamp(&self) -> u32 {
+        self.timestamp
+    }
+    pub fn debug(&self) -> String {
+        format!("RxMeta: {:?}", self)
+    }
+}
+
+impl Display for RxMeta {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "[{}] RxData: src={} dst={} rssi={} data=\"{}\"",
-            self.id,
-            format_mac(&self.src_addr),
-            format_mac(&self.dst_addr),
-            self.rssi,
-            display_vec::<64, MAX_DATA_LEN>(&self.data)
+            "channel={} noise_floor={} rate={} sig_mode={} timestamp={}",
+            self.channel, self.noise_floor, self.rate, self.sig_mode, self.timestamp
         )
     }
 }
 
-impl defmt::Format for RxData {
+impl defmt::Format for RxMeta {
     fn format(&self, fmt: defmt::Formatter) {
         defmt::write!(
             fmt,
-            "[{}] RxData: src={} dst={} rssi={} data=\"{}\"",
-            self.id,
-            format_mac(&self.src_addr),
-            format_mac(&self.dst_addr),
-            self.rssi,
-            display_vec::<64, MAX_DATA_LEN>(&self.data)
+            "channel={} noise_floor={} rate={} sig_mode={} timestamp={}",
+            self.channel,
+            self.noise_floor,
+            self.rate,
+            self.sig_mode,
+            self.timestamp
         )
     }
 }
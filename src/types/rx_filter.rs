@@ -0,0 +1,363 @@
+#[cfg(feature = "js")]
+use rquickjs::{class::Trace, ArrayBuffer, Ctx, Exception, JsLifetime};
+
+#[cfg(feature = "js")]
+use crate::util::buf_to_array;
+
+use crate::types::validate::ValidationError;
+use crate::types::{RxData, RxDataRef};
+use crate::util::{display_slice, format_mac};
+
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+/// Maximum rules per RxFilter
+pub const MAX_RX_FILTER_RULES: usize = 8;
+/// Maximum payload prefix per rule
+pub const MAX_RX_FILTER_PREFIX: usize = 8;
+
+const BROADCAST_ADDR: [u8; 6] = [0xff; 6];
+
+/// Single allow/deny rule - all set conditions must match
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct RxFilterRule {
+    /// Forward (true) or drop (false) matching frames
+    pub allow: bool,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub src_addr: Option<[u8; 6]>,
+    /// Match broadcast (true) or unicast (false) dst_addr
+    pub broadcast: Option<bool>,
+    /// Match rssi >= min_rssi
+    pub min_rssi: Option<i32>,
+    /// Match payload starting with prefix (empty matches any payload)
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub prefix: heapless::Vec<u8, MAX_RX_FILTER_PREFIX>,
+}
+
+impl RxFilterRule {
+    pub fn matches(&self, src_addr: &[u8; 6], dst_addr: &[u8; 6], rssi: i32, data: &[u8]) -> bool {
+        self.src_addr.is_none_or(|a| &a == src_addr)
+            && self
+                .broadcast
+                .is_none_or(|b| b == (dst_addr == &BROADCAST_ADDR))
+            && self.min_rssi.is_none_or(|r| rssi >= r)
+            && data.starts_with(&self.prefix)
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl RxFilterRule {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        allow: bool,
+        src_addr: Option<ArrayBuffer<'_>>,
+        broadcast: Option<bool>,
+        min_rssi: Option<i32>,
+        prefix: Option<ArrayBuffer<'_>>,
+    ) -> rquickjs::Result<Self> {
+        let src_addr: Option<[u8; 6]> = match src_addr {
+            Some(buf) => Some(buf_to_array::<6>(&ctx, &buf, "Invalid src_addr")?),
+            None => None,
+        };
+        let prefix = match prefix {
+            Some(buf) => heapless::Vec::from_slice(buf.as_bytes().unwrap_or(&[]))
+                .map_err(|_| Exception::throw_message(&ctx, "prefix invalid"))?,
+            None => heapless::Vec::new(),
+        };
+        Ok(Self {
+            allow,
+            src_addr,
+            broadcast,
+            min_rssi,
+            prefix,
+        })
+    }
+    #[qjs(get, rename = "allow")]
+    pub fn get_allow(&self) -> bool {
+        self.allow
+    }
+    #[qjs(get, rename = "src_addr")]
+    pub fn get_src_addr<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<Option<ArrayBuffer<'js>>> {
+        self.src_addr
+            .map(|a| ArrayBuffer::new_copy(ctx, a.as_slice()))
+            .transpose()
+    }
+    #[qjs(get, rename = "broadcast")]
+    pub fn get_broadcast(&self) -> Option<bool> {
+        self.broadcast
+    }
+    #[qjs(get, rename = "min_rssi")]
+    pub fn get_min_rssi(&self) -> Option<i32> {
+        self.min_rssi
+    }
+    #[qjs(get, rename = "prefix")]
+    pub fn get_prefix<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<ArrayBuffer<'js>> {
+        ArrayBuffer::new_copy(ctx, self.prefix.as_slice())
+    }
+    pub fn debug(&self) -> String {
+        format!("RxFilterRule: {:?}", self)
+    }
+}
+
+impl Display for RxFilterRule {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", if self.allow { "allow" } else { "deny" })?;
+        if let Some(a) = &self.src_addr {
+            write!(f, " src={}", format_mac(a))?;
+        }
+        if let Some(b) = self.broadcast {
+            write!(f, " broadcast={}", b)?;
+        }
+        if let Some(r) = self.min_rssi {
+            write!(f, " min_rssi={}", r)?;
+        }
+        if !self.prefix.is_empty() {
+            write!(f, " prefix=\"{}\"", display_slice::<16>(&self.prefix))?;
+        }
+        Ok(())
+    }
+}
+
+impl defmt::Format for RxFilterRule {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{}", if self.allow { "allow" } else { "deny" });
+        if let Some(a) = &self.src_addr {
+            defmt::write!(fmt, " src={}", format_mac(a));
+        }
+        if let Some(b) = self.broadcast {
+            defmt::write!(fmt, " broadcast={}", b);
+        }
+        if let Some(r) = self.min_rssi {
+            defmt::write!(fmt, " min_rssi={}", r);
+        }
+        if !self.prefix.is_empty() {
+            defmt::write!(fmt, " prefix=\"{}\"", display_slice::<16>(&self.prefix));
+        }
+    }
+}
+
+// Server -> Hub :: Set receive filter (SetRxFilter)
+//
+// Rules are evaluated in order for each received ESP-NOW frame - the first
+// matching rule decides whether the frame is forwarded as RxData, frames
+// matching no rule use default_allow. The filter replaces any previous
+// filter - no rules with default_allow set forwards every frame (the
+// default at hub start).
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct RxFilter {
+    pub id: u32,
+    pub default_allow: bool,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub rules: heapless::Vec<RxFilterRule, MAX_RX_FILTER_RULES>,
+}
+
+impl RxFilter {
+    /// Forward every frame
+    pub const fn allow_all(id: u32) -> Self {
+        Self {
+            id,
+            default_allow: true,
+            rules: heapless::Vec::new(),
+        }
+    }
+
+    /// Evaluate filter for received frame
+    pub fn allows(&self, src_addr: &[u8; 6], dst_addr: &[u8; 6], rssi: i32, data: &[u8]) -> bool {
+        self.rules
+            .iter()
+            .find(|r| r.matches(src_addr, dst_addr, rssi, data))
+            .map_or(self.default_allow, |r| r.allow)
+    }
+
+    pub fn allows_rx(&self, rx: &RxData) -> bool {
+        self.allows(&rx.src_addr, &rx.dst_addr, rx.rssi, &rx.data)
+    }
+
+    pub fn allows_rx_ref(&self, rx: &RxDataRef<'_>) -> bool {
+        self.allows(&rx.src_addr, &rx.dst_addr, rx.rssi, rx.data)
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl RxFilter {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        id: u32,
+        default_allow: bool,
+        rules: Vec<RxFilterRule>,
+    ) -> rquickjs::Result<Self> {
+        Ok(Self {
+            id,
+            default_allow,
+            rules: heapless::Vec::from_slice(&rules)
+                .map_err(|_| Exception::throw_message(&ctx, "Too many rules"))?,
+        })
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "default_allow")]
+    pub fn get_default_allow(&self) -> bool {
+        self.default_allow
+    }
+    #[qjs(get, rename = "rules")]
+    pub fn get_rules(&self) -> Vec<RxFilterRule> {
+        self.rules.to_vec()
+    }
+    #[qjs(rename = "allows")]
+    pub fn js_allows(&self, rx: RxData) -> bool {
+        self.allows_rx(&rx)
+    }
+    pub fn debug(&self) -> String {
+        format!("RxFilter: {:?}", self)
+    }
+}
+
+impl Display for RxFilter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] RxFilter: default_allow={} [",
+            self.id, self.default_allow
+        )?;
+        for (i, r) in self.rules.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", r)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl defmt::Format for RxFilter {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] RxFilter: default_allow={} {}",
+            self.id,
+            self.default_allow,
+            self.rules.as_slice()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{A, B};
+
+    const HUB: [u8; 6] = [0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6];
+
+    fn rule(allow: bool) -> RxFilterRule {
+        RxFilterRule {
+            allow,
+            src_addr: None,
+            broadcast: None,
+            min_rssi: None,
+            prefix: heapless::Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_allow_all() {
+        let f = RxFilter::allow_all(1);
+        assert!(f.allows(&A, &BROADCAST_ADDR, -100, b""));
+        assert!(f.allows(&B, &HUB, 0, b"data"));
+    }
+
+    #[test]
+    fn test_first_match() {
+        let rules = [
+            // Drop broadcasts from A
+            RxFilterRule {
+                src_addr: Some(A),
+                broadcast: Some(true),
+                ..rule(false)
+            },
+            // Forward anything else from A
+            RxFilterRule {
+                src_addr: Some(A),
+                ..rule(true)
+            },
+            // Forward strong unicast frames with prefix
+            RxFilterRule {
+                broadcast: Some(false),
+                min_rssi: Some(-70),
+                prefix: heapless::Vec::from_slice(b"T:").unwrap(),
+                ..rule(true)
+            },
+        ];
+        let f = RxFilter {
+            id: 1,
+            default_allow: false,
+            rules: heapless::Vec::from_slice(&rules).unwrap(),
+        };
+        assert!(!f.allows(&A, &BROADCAST_ADDR, -40, b"T:1"));
+        assert!(f.allows(&A, &HUB, -90, b""));
+        assert!(f.allows(&B, &HUB, -70, b"T:21.5"));
+        assert!(!f.allows(&B, &HUB, -71, b"T:21.5"));
+        assert!(!f.allows(&B, &HUB, -40, b"H:60"));
+        assert!(!f.allows(&B, &HUB, -40, b"T"));
+        assert!(!f.allows(&B, &BROADCAST_ADDR, -40, b"T:21.5"));
+    }
+
+    #[test]
+    fn test_rx() {
+        let f = RxFilter {
+            id: 1,
+            default_allow: true,
+            rules: heapless::Vec::from_slice(&[RxFilterRule {
+                src_addr: Some(B),
+                ..rule(false)
+            }])
+            .unwrap(),
+        };
+        let rx = RxData {
+            id: 2,
+            src_addr: B,
+            dst_addr: HUB,
+            data: heapless::Vec::from_slice(b"hello").unwrap(),
+            rssi: -50,
+            meta: None,
+        };
+        assert!(!f.allows_rx(&rx));
+        assert!(!f.allows_rx_ref(&(&rx).into()));
+        assert!(f.allows_rx(&RxData { src_addr: A, ..rx }));
+    }
+
+    #[test]
+    fn test_filter_fits_frame() {
+        let rule = RxFilterRule {
+            allow: true,
+            src_addr: Some(A),
+            broadcast: Some(false),
+            min_rssi: Some(i32::MIN),
+            prefix: heapless::Vec::from_slice(&[0xff; MAX_RX_FILTER_PREFIX]).unwrap(),
+        };
+        let f = RxFilter {
+            id: u32::MAX,
+            default_allow: false,
+            rules: core::iter::repeat_n(rule, MAX_RX_FILTER_RULES).collect(),
+        };
+        let msg = crate::Msg::SetRxFilter(f);
+        let mut buf = [0_u8; crate::MAX_MSG_LEN];
+        assert!(msg.to_slice(&mut buf).is_ok());
+    }
+}
@@ -0,0 +1,325 @@
+#[cfg(feature = "js")]
+use rquickjs::{class::Trace, JsLifetime};
+
+use crate::types::validate::ValidationError;
+
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+// Server -> Hub :: Request HubStats
+//
+// The hub replies with HubStats immediately. If interval is set the hub
+// also pushes HubStats every interval ms (Some(0) stops periodic reports).
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct GetStats {
+    pub id: u32,
+    pub interval: Option<u32>,
+}
+
+impl GetStats {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl GetStats {
+    #[qjs(constructor)]
+    pub fn new(id: u32, interval: Option<u32>) -> Self {
+        Self { id, interval }
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "interval")]
+    pub fn get_interval(&self) -> Option<u32> {
+        self.interval
+    }
+    pub fn debug(&self) -> String {
+        format!("GetStats: {:?}", self)
+    }
+}
+
+impl Display for GetStats {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[{}] GetStats: interval={:?}", self.id, self.interval)
+    }
+}
+
+impl defmt::Format for GetStats {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "[{}] GetStats: interval={:?}", self.id, self.interval)
+    }
+}
+
+// Hub -> Server :: Hub statistics (reply to GetStats or periodic)
+//
+// Counters are cumulative since hub boot (wrapping) - use uptime to detect a
+// restart.
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct HubStats {
+    pub id: u32,
+    /// ESP-NOW frames sent (send callback success)
+    pub tx_ok: u32,
+    /// ESP-NOW frames failed (send error or callback failure)
+    pub tx_fail: u32,
+    /// ESP-NOW frames received
+    pub rx: u32,
+    /// Frames dropped (queue full)
+    pub dropped: u32,
+    /// Server frames failed to decode
+    pub decode_errors: u32,
+    /// TX queue high-water mark
+    pub tx_queue_hwm: u16,
+    /// RX queue high-water mark
+    pub rx_queue_hwm: u16,
+    /// Uptime (s)
+    pub uptime: u32,
+    /// Free heap (bytes)
+    pub free_heap: u32,
+}
+
+impl HubStats {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl HubStats {
+    #[qjs(constructor)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: u32,
+        tx_ok: u32,
+        tx_fail: u32,
+        rx: u32,
+        dropped: u32,
+        decode_errors: u32,
+        tx_queue_hwm: u16,
+        rx_queue_hwm: u16,
+        uptime: u32,
+        free_heap: u32,
+    ) -> Self {
+        Self {
+            id,
+            tx_ok,
+            tx_fail,
+            rx,
+            dropped,
+            decode_errors,
+            tx_queue_hwm,
+            rx_queue_hwm,
+            uptime,
+            free_heap,
+        }
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "tx_ok")]
+    pub fn get_tx_ok(&self) -> u32 {
+        self.tx_ok
+    }
+    #[qjs(get, rename = "tx_fail")]
+    pub fn get_tx_fail(&self) -> u32 {
+        self.tx_fail
+    }
+    #[qjs(get, rename = "rx")]
+    pub fn get_rx(&self) -> u32 {
+        self.rx
+    }
+    #[qjs(get, rename = "dropped")]
+    pub fn get_dropped(&self) -> u32 {
+        self.dropped
+    }
+    #[qjs(get, rename = "decode_errors")]
+    pub fn get_decode_errors(&self) -> u32 {
+        self.decode_errors
+    }
+    #[qjs(get, rename = "tx_queue_hwm")]
+    pub fn get_tx_queue_hwm(&self) -> u16 {
+        self.tx_queue_hwm
+    }
+    #[qjs(get, rename = "rx_queue_hwm")]
+    pub fn get_rx_queue_hwm(&self) -> u16 {
+        self.rx_queue_hwm
+    }
+    #[qjs(get, rename = "uptime")]
+    pub fn get_uptime(&self) -> u32 {
+        self.uptime
+    }
+    #[qjs(get, rename = "free_heap")]
+    pub fn get_free_heap(&self) -> u32 {
+        self.free_heap
+    }
+    pub fn debug(&self) -> String {
+        format!("HubStats: {:?}", self)
+    }
+}
+
+impl Display for HubStats {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] HubStats: tx_ok={} tx_fail={} rx={} dropped={} decode_errors={} tx_queue_hwm={} rx_queue_hwm={} uptime={} free_heap={}",
+            self.id,
+            self.tx_ok,
+            self.tx_fail,
+            self.rx,
+            self.dropped,
+            self.decode_errors,
+            self.tx_queue_hwm,
+            self.rx_queue_hwm,
+            self.uptime,
+            self.free_heap
+        )
+    }
+}
+
+impl defmt::Format for HubStats {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] HubStats: tx_ok={} tx_fail={} rx={} dropped={} decode_errors={} tx_queue_hwm={} rx_queue_hwm={} uptime={} free_heap={}",
+            self.id,
+            self.tx_ok,
+            self.tx_fail,
+            self.rx,
+            self.dropped,
+            self.decode_errors,
+            self.tx_queue_hwm,
+            self.rx_queue_hwm,
+            self.uptime,
+            self.free_heap
+        )
+    }
+}
+
+/// Hub counters - incremented by firmware and reported as HubStats
+///
+/// >>> counters.tx(result.is_ok());
+/// >>> counters.tx_queue(queue.len());
+/// >>> Msg::HubStats(counters.report(next_id(), uptime, free_heap))
+#[derive(Clone, Default, PartialEq, Eq, Debug, defmt::Format)]
+pub struct HubCounters {
+    pub tx_ok: u32,
+    pub tx_fail: u32,
+    pub rx: u32,
+    pub dropped: u32,
+    pub decode_errors: u32,
+    pub tx_queue_hwm: u16,
+    pub rx_queue_hwm: u16,
+}
+
+impl HubCounters {
+    pub const fn new() -> Self {
+        Self {
+            tx_ok: 0,
+            tx_fail: 0,
+            rx: 0,
+            dropped: 0,
+            decode_errors: 0,
+            tx_queue_hwm: 0,
+            rx_queue_hwm: 0,
+        }
+    }
+
+    /// Count ESP-NOW send result
+    pub fn tx(&mut self, ok: bool) {
+        if ok {
+            self.tx_ok = self.tx_ok.wrapping_add(1);
+        } else {
+            self.tx_fail = self.tx_fail.wrapping_add(1);
+        }
+    }
+
+    pub fn rx(&mut self) {
+        self.rx = self.rx.wrapping_add(1);
+    }
+
+    pub fn dropped(&mut self) {
+        self.dropped = self.dropped.wrapping_add(1);
+    }
+
+    pub fn decode_error(&mut self) {
+        self.decode_errors = self.decode_errors.wrapping_add(1);
+    }
+
+    /// Record TX queue depth
+    pub fn tx_queue(&mut self, len: usize) {
+        self.tx_queue_hwm = self.tx_queue_hwm.max(len.min(u16::MAX as usize) as u16);
+    }
+
+    /// Record RX queue depth
+    pub fn rx_queue(&mut self, len: usize) {
+        self.rx_queue_hwm = self.rx_queue_hwm.max(len.min(u16::MAX as usize) as u16);
+    }
+
+    pub fn report(&self, id: u32, uptime: u32, free_heap: u32) -> HubStats {
+        HubStats {
+            id,
+            tx_ok: self.tx_ok,
+            tx_fail: self.tx_fail,
+            rx: self.rx,
+            dropped: self.dropped,
+            decode_errors: self.decode_errors,
+            tx_queue_hwm: self.tx_queue_hwm,
+            rx_queue_hwm: self.rx_queue_hwm,
+            uptime,
+            free_heap,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let mut c = HubCounters::new();
+        c.tx(true);
+        c.tx(true);
+        c.tx(false);
+        c.rx();
+        c.dropped();
+        c.decode_error();
+        c.tx_queue(4);
+        c.tx_queue(2);
+        c.rx_queue(100_000);
+        let s = c.report(1, 60, 1024);
+        assert_eq!((s.tx_ok, s.tx_fail, s.rx), (2, 1, 1));
+        assert_eq!((s.dropped, s.decode_errors), (1, 1));
+        assert_eq!((s.tx_queue_hwm, s.rx_queue_hwm), (4, u16::MAX));
+        assert_eq!((s.uptime, s.free_heap), (60, 1024));
+        c.reset();
+        assert_eq!(c, HubCounters::default());
+    }
+
+    #[test]
+    fn test_counters_wrap() {
+        let mut c = HubCounters {
+            rx: u32::MAX,
+            ..HubCounters::new()
+        };
+        c.rx();
+        assert_eq!(c.rx, 0);
+    }
+}
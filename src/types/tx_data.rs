@@ -4,13 +4,24 @@ use rquickjs::{class::Trace, ArrayBuffer, Ctx, Exception, JsLifetime};
 #[cfg(feature = "js")]
 use crate::util::buf_to_array;
 
-use crate::util::{display_vec, format_mac};
+use crate::types::validate::ValidationError;
+use crate::types::TxDataRef;
 use crate::MAX_DATA_LEN;
 
 use core::fmt::Display;
 use serde::{Deserialize, Serialize};
 
 // Server -> Hub :: TX esp-now msg
+//
+// With defer set the hub queues the message until dst_addr is awake (see
+// deferred).
+//
+// With schedule set the hub holds the message and sends it after a delay or
+// at an absolute hub time (ms, as reported in Pong.timestamp) - the Ack is
+// sent when the message is scheduled (Ack ScheduleFull if the hub cannot
+// hold it) and TxStatus when it is sent. A scheduled send can be cancelled
+// with CancelSend using the TxData id. schedule is encoded as an optional
+// trailer (see RxData meta).
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
@@ -21,6 +32,15 @@ pub struct TxData {
     #[cfg_attr(feature = "js", qjs(skip_trace))]
     pub data: heapless::Vec<u8, MAX_DATA_LEN>,
     pub defer: bool,
+    #[serde(skip)]
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub schedule: Option<Schedule>,
+}
+
+impl TxData {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        TxDataRef::from(self).validate()
+    }
 }
 
 #[cfg(feature = "js")]
@@ -33,14 +53,18 @@ impl TxData {
         dst_addr: ArrayBuffer<'_>,
         data: ArrayBuffer<'_>,
         defer: bool,
+        schedule: Option<Schedule>,
     ) -> rquickjs::Result<Self> {
-        Ok(Self {
+        let m = Self {
             id,
             dst_addr: buf_to_array::<6>(&ctx, &dst_addr, "Invalid dst_addr")?,
             data: heapless::Vec::from_slice(data.as_bytes().unwrap_or(&[]))
                 .map_err(|_| Exception::throw_message(&ctx, "data invalid"))?,
             defer,
-        })
+            schedule,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
     }
     pub fn debug(&self) -> String {
         format!("TxData: {:?}", self)
@@ -49,26 +73,223 @@ impl TxData {
 
 impl Display for TxData {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(
-            f,
-            "[{}] TxData: dst={} defer={} data=\"{}\"",
-            self.id,
-            format_mac(&self.dst_addr),
-            self.defer,
-            display_vec::<64, MAX_DATA_LEN>(&self.data)
-        )
+        write!(f, "{}", TxDataRef::from(self))
     }
 }
 
 impl defmt::Format for TxData {
     fn format(&self, fmt: defmt::Formatter) {
-        defmt::write!(
-            fmt,
-            "[{}] TxData: dst={} defer={} data=\"{}\"",
-            self.id,
-            format_mac(&self.dst_addr),
-            self.defer,
-            display_vec::<64, MAX_DATA_LEN>(&self.data)
-        )
+        defmt::write!(fmt, "{}", TxDataRef::from(self))
+    }
+}
+
+/// Send time for scheduled TxData
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct Schedule {
+    /// Hub time (absolute) or delay from receipt (ms)
+    pub time: u64,
+    pub absolute: bool,
+}
+
+impl Schedule {
+    pub const fn delay(ms: u32) -> Self {
+        Self {
+            time: ms as u64,
+            absolute: false,
+        }
+    }
+
+    pub const fn at(time: u64) -> Self {
+        Self {
+            time,
+            absolute: true,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        if self.absolute {
+            "at"
+        } else {
+            "delay"
+        }
+    }
+
+    /// Hub time to send for message received at now
+    pub fn due(&self, now: u64) -> u64 {
+        if self.absolute {
+            self.time
+        } else {
+            now.saturating_add(self.time)
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl Schedule {
+    #[qjs(constructor)]
+    pub fn new(time: u64, absolute: bool) -> Self {
+        Self { time, absolute }
+    }
+    #[qjs(static, rename = "delay")]
+    pub fn js_delay(ms: u32) -> Self {
+        Self::delay(ms)
+    }
+    #[qjs(static, rename = "at")]
+    pub fn js_at(time: u64) -> Self {
+        Self::at(time)
+    }
+    #[qjs(get, rename = "time")]
+    pub fn get_time(&self) -> u64 {
+        self.time
+    }
+    #[qjs(get, rename = "absolute")]
+    pub fn get_absolute(&self) -> bool {
+        self.absolute
+    }
+    pub fn debug(&self) -> String {
+        format!("Schedule: {:?}", self)
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}={}", self.label(), self.time)
+    }
+}
+
+impl defmt::Format for Schedule {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{}={}", self.label(), self.time)
+    }
+}
+
+// Server -> Hub :: Cancel scheduled TxData (Ack ScheduleNotFound if it has
+// already been sent or was not scheduled)
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct CancelSend {
+    pub id: u32,
+    pub tx_id: u32,
+}
+
+impl CancelSend {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl CancelSend {
+    #[qjs(constructor)]
+    pub fn new(id: u32, tx_id: u32) -> Self {
+        Self { id, tx_id }
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "tx_id")]
+    pub fn get_tx_id(&self) -> u32 {
+        self.tx_id
+    }
+    pub fn debug(&self) -> String {
+        format!("CancelSend: {:?}", self)
+    }
+}
+
+impl Display for CancelSend {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[{}] CancelSend: tx_id={}", self.id, self.tx_id)
+    }
+}
+
+impl defmt::Format for CancelSend {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "[{}] CancelSend: tx_id={}", self.id, self.tx_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_due() {
+        assert_eq!(Schedule::delay(30_000).due(1000), 31_000);
+        assert_eq!(Schedule::at(500).due(1000), 500);
+        assert_eq!(
+            Schedule {
+                time: u64::MAX,
+                absolute: false
+            }
+            .due(1),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn test_scheduled_fits_frame() {
+        let tx = TxData {
+            id: u32::MAX,
+            dst_addr: [0xff; 6],
+            data: heapless::Vec::from_slice(&[0xff; MAX_DATA_LEN]).unwrap(),
+            defer: true,
+            schedule: Some(Schedule::at(u64::MAX)),
+        };
+        let msg = crate::Msg::Send(tx);
+        let mut buf = [0_u8; crate::MAX_MSG_LEN];
+        assert!(msg.to_slice(&mut buf).is_ok());
+    }
+
+    #[test]
+    fn test_max_msg_fits_frame() {
+        let peer = crate::PeerInfo {
+            id: u32::MAX,
+            peer_address: [0xff; 6],
+            lmk: Some([0xff; 16]),
+            channel: Some(0xff),
+            encrypt: true,
+        };
+        let msgs = [
+            crate::Msg::Recv(crate::RxData {
+                id: u32::MAX,
+                src_addr: [0xff; 6],
+                dst_addr: [0xff; 6],
+                data: heapless::Vec::from_slice(&[0xff; MAX_DATA_LEN]).unwrap(),
+                rssi: i32::MIN,
+                meta: Some(crate::RxMeta {
+                    channel: 0xff,
+                    noise_floor: i8::MIN,
+                    rate: 0xff,
+                    sig_mode: 0xff,
+                    timestamp: u32::MAX,
+                }),
+            }),
+            crate::Msg::PeerList(crate::PeerList {
+                id: u32::MAX,
+                rx_id: u32::MAX,
+                offset: 0xff,
+                total: 0xff,
+                peers: core::iter::repeat_n(peer, crate::PEER_LIST_LEN).collect(),
+            }),
+        ];
+        for msg in &msgs {
+            let mut buf = [0_u8; crate::MAX_MSG_LEN];
+            assert!(msg.to_slice(&mut buf).is_ok());
+            let mut frame = [0_u8; crate::framing::MAX_FRAME_LEN];
+            let checksum = crate::framing::Checksum::Crc16;
+            assert!(crate::framing::encode_frame_with(msg, checksum, &mut frame).is_ok());
+        }
+        // Bound is tight
+        let mut buf = [0_u8; crate::MAX_MSG_LEN - 1];
+        assert!(msgs.iter().any(|msg| msg.to_slice(&mut buf).is_err()));
     }
 }
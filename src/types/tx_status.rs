@@ -0,0 +1,119 @@
+#[cfg(feature = "js")]
+use rquickjs::{class::Trace, ArrayBuffer, Ctx, JsLifetime};
+
+#[cfg(feature = "js")]
+use crate::util::buf_to_array;
+
+use crate::types::validate::ValidationError;
+use crate::types::TxData;
+use crate::util::format_mac;
+
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+// Hub -> Server :: ESP-NOW send callback result for TxData
+//
+// Sent once per TxData (after the Ack) when the radio reports the final
+// result for dst_addr. retries is the number of hub level resends before
+// the final result.
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct TxStatus {
+    pub id: u32,
+    pub tx_id: u32,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub dst_addr: [u8; 6],
+    pub success: bool,
+    pub retries: u8,
+}
+
+impl TxStatus {
+    /// Result for tx
+    pub fn from_tx(id: u32, tx: &TxData, success: bool, retries: u8) -> Self {
+        Self {
+            id,
+            tx_id: tx.id,
+            dst_addr: tx.dst_addr,
+            success,
+            retries,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl TxStatus {
+    #[qjs(constructor)]
+    pub fn new(
+        ctx: Ctx<'_>,
+        id: u32,
+        tx_id: u32,
+        dst_addr: ArrayBuffer<'_>,
+        success: bool,
+        retries: u8,
+    ) -> rquickjs::Result<Self> {
+        Ok(Self {
+            id,
+            tx_id,
+            dst_addr: buf_to_array::<6>(&ctx, &dst_addr, "Invalid dst_addr")?,
+            success,
+            retries,
+        })
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "tx_id")]
+    pub fn get_tx_id(&self) -> u32 {
+        self.tx_id
+    }
+    #[qjs(get, rename = "dst_addr")]
+    pub fn get_dst_addr<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<ArrayBuffer<'js>> {
+        ArrayBuffer::new_copy(ctx, self.dst_addr.as_slice())
+    }
+    #[qjs(get, rename = "success")]
+    pub fn get_success(&self) -> bool {
+        self.success
+    }
+    #[qjs(get, rename = "retries")]
+    pub fn get_retries(&self) -> u8 {
+        self.retries
+    }
+    pub fn debug(&self) -> String {
+        format!("TxStatus: {:?}", self)
+    }
+}
+
+impl Display for TxStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "[{}] TxStatus: tx_id={} dst={} success={} retries={}",
+            self.id,
+            self.tx_id,
+            format_mac(&self.dst_addr),
+            self.success,
+            self.retries
+        )
+    }
+}
+
+impl defmt::Format for TxStatus {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "[{}] TxStatus: tx_id={} dst={} success={} retries={}",
+            self.id,
+            self.tx_id,
+            format_mac(&self.dst_addr),
+            self.success,
+            self.retries
+        )
+    }
+}
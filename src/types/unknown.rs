@@ -0,0 +1,75 @@
+#[cfg(feature = "js")]
+use rquickjs::{class::Trace, ArrayBuffer, Ctx, Exception, JsLifetime};
+
+use crate::types::validate::ValidationError;
+use crate::types::UnknownMsgRef;
+use crate::types::{Ack, AckError};
+use crate::MAX_MSG_LEN;
+
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+// Bidirectional :: Msg with envelope kind not known to this version
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "js", derive(Trace, JsLifetime), rquickjs::class())]
+pub struct UnknownMsg {
+    pub id: u32,
+    pub kind: u8,
+    #[cfg_attr(feature = "js", qjs(skip_trace))]
+    pub bytes: heapless::Vec<u8, MAX_MSG_LEN>,
+}
+
+impl UnknownMsg {
+    /// Negative acknowledgement for unsupported message
+    pub fn nack(&self, id: u32) -> Ack {
+        Ack::error(id, self.id, AckError::Unsupported)
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        UnknownMsgRef::from(self).validate()
+    }
+}
+
+#[cfg(feature = "js")]
+#[rquickjs::methods]
+impl UnknownMsg {
+    #[qjs(constructor)]
+    pub fn new(ctx: Ctx<'_>, id: u32, kind: u8, bytes: ArrayBuffer<'_>) -> rquickjs::Result<Self> {
+        let m = Self {
+            id,
+            kind,
+            bytes: heapless::Vec::from_slice(bytes.as_bytes().unwrap_or(&[]))
+                .map_err(|_| Exception::throw_message(&ctx, "bytes invalid"))?,
+        };
+        m.validate().map_err(|e| e.throw(&ctx))?;
+        Ok(m)
+    }
+    #[qjs(get, rename = "id")]
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+    #[qjs(get, rename = "kind")]
+    pub fn get_kind(&self) -> u8 {
+        self.kind
+    }
+    #[qjs(get, rename = "bytes")]
+    pub fn get_bytes<'js>(&self, ctx: Ctx<'js>) -> rquickjs::Result<ArrayBuffer<'js>> {
+        ArrayBuffer::new_copy(ctx, self.bytes.as_slice())
+    }
+    pub fn debug(&self) -> String {
+        format!("UnknownMsg: {:?}", self)
+    }
+}
+
+impl Display for UnknownMsg {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", UnknownMsgRef::from(self))
+    }
+}
+
+impl defmt::Format for UnknownMsg {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{}", UnknownMsgRef::from(self))
+    }
+}
@@ -0,0 +1,142 @@
+#[cfg(feature = "js")]
+use rquickjs::{Ctx, Exception};
+
+use crate::types::msg::decode_envelope;
+use crate::types::MIN_BROADCAST_INTERVAL;
+use crate::{Ack, AckError};
+
+use core::fmt::Display;
+
+// Semantic validation :: constraints not enforced by the wire format
+//
+// Every type has validate() (Msg/MsgRef dispatch to the contained type) and
+// the JS constructors throw on invalid values. The hub decodes and validates
+// a received frame in a single call:
+//
+// >>> let msg = match MsgRef::from_slice_validated(&frame) {
+// >>>     Ok(msg) => msg,
+// >>>     Err(e) => { if let Some(ack) = e.ack(next_id()) { send(Msg::Ack(ack)) }; continue }
+// >>> };
+
+/// Highest WiFi channel
+pub const MAX_CHANNEL: u8 = 14;
+/// ESP-NOW peer table size (ESP_NOW_MAX_TOTAL_PEER_NUM)
+pub const MAX_PEERS: usize = 20;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum ValidationError {
+    /// Channel outside 1..=MAX_CHANNEL (peer channel 0 is the current channel)
+    InvalidChannel(u8),
+    /// Encrypted peer without LMK
+    MissingLmk,
+    /// Broadcast address cannot be an encrypted peer
+    EncryptedBroadcast,
+    EmptyData,
+    /// Periodic interval below MIN_BROADCAST_INTERVAL
+    InvalidInterval(u32),
+    /// Successful Ack with an error
+    AckStatus,
+    /// PeerList page extends past total
+    PeerListRange,
+    /// UnknownMsg kind is a message this version decodes
+    KnownKind(u8),
+    /// UnknownMsg bytes do not start with its id
+    UnknownId,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ValidationError::InvalidChannel(c) => {
+                write!(f, "invalid channel {} (1-{})", c, MAX_CHANNEL)
+            }
+            ValidationError::MissingLmk => write!(f, "encrypt requires lmk"),
+            ValidationError::EncryptedBroadcast => {
+                write!(f, "broadcast peer cannot be encrypted")
+            }
+            ValidationError::EmptyData => write!(f, "data is empty"),
+            ValidationError::InvalidInterval(i) => write!(
+                f,
+                "interval {}ms below minimum {}ms",
+                i, MIN_BROADCAST_INTERVAL
+            ),
+            ValidationError::AckStatus => write!(f, "successful ack has an error"),
+            ValidationError::PeerListRange => write!(f, "peer list page exceeds total"),
+            ValidationError::KnownKind(k) => write!(f, "kind {} is not an unknown message", k),
+            ValidationError::UnknownId => write!(f, "id does not match message bytes"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+#[cfg(not(feature = "std"))]
+impl core::error::Error for ValidationError {}
+
+#[cfg(feature = "js")]
+impl ValidationError {
+    /// JS exception for constructors
+    pub fn throw(&self, ctx: &Ctx<'_>) -> rquickjs::Error {
+        Exception::throw_message(ctx, &self.to_string())
+    }
+}
+
+impl From<ValidationError> for AckError {
+    fn from(e: ValidationError) -> Self {
+        match e {
+            ValidationError::InvalidChannel(_) => AckError::InvalidChannel,
+            _ => AckError::InvalidArg,
+        }
+    }
+}
+
+/// Received frame rejected by from_slice_validated
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub struct InvalidMsg {
+    /// Msg id (None if it could not be decoded)
+    pub id: Option<u32>,
+    pub error: AckError,
+}
+
+impl InvalidMsg {
+    pub(crate) fn decode(buf: &[u8], error: AckError) -> Self {
+        // All message bodies start with the id
+        let id = decode_envelope(buf)
+            .ok()
+            .and_then(|(_, body)| postcard::from_bytes(body).ok());
+        Self { id, error }
+    }
+
+    /// Ack to report the error (None if the Msg id is unknown)
+    pub fn ack(&self, id: u32) -> Option<Ack> {
+        self.id.map(|rx_id| Ack::error(id, rx_id, self.error))
+    }
+}
+
+impl Display for InvalidMsg {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "InvalidMsg: id={:?} error={}", self.id, self.error)
+    }
+}
+
+pub(crate) fn check_channel(channel: u8) -> Result<(), ValidationError> {
+    match channel {
+        1..=MAX_CHANNEL => Ok(()),
+        c => Err(ValidationError::InvalidChannel(c)),
+    }
+}
+
+pub(crate) fn check_data(data: &[u8]) -> Result<(), ValidationError> {
+    match data.is_empty() {
+        true => Err(ValidationError::EmptyData),
+        false => Ok(()),
+    }
+}
+
+pub(crate) fn check_interval(interval: Option<u32>) -> Result<(), ValidationError> {
+    match interval {
+        Some(i) if i < MIN_BROADCAST_INTERVAL => Err(ValidationError::InvalidInterval(i)),
+        _ => Ok(()),
+    }
+}
@@ -1,10 +1,12 @@
+mod crc;
 mod format_mac;
 mod js;
 mod register;
 mod view;
 
+pub use crc::crc16;
 pub use format_mac::{format_mac, parse_mac};
-pub use view::display_vec;
+pub use view::{display_slice, display_vec};
 
 #[cfg(feature = "js")]
 pub use js::{buf_to_array, js_format_mac, js_parse_mac};
@@ -13,10 +13,36 @@ pub fn register_espnow(ctx: &Ctx<'_>) -> anyhow::Result<()> {
     rquickjs::Class::<HubConfig>::define(&espnow)?;
     rquickjs::Class::<TxData>::define(&espnow)?;
     rquickjs::Class::<RxData>::define(&espnow)?;
+    rquickjs::Class::<RxMeta>::define(&espnow)?;
     rquickjs::Class::<BroadcastData>::define(&espnow)?;
     rquickjs::Class::<PeerInfo>::define(&espnow)?;
     rquickjs::Class::<PeerAddress>::define(&espnow)?;
     rquickjs::Class::<Ack>::define(&espnow)?;
+    rquickjs::Class::<UnknownMsg>::define(&espnow)?;
+    rquickjs::Class::<Ping>::define(&espnow)?;
+    rquickjs::Class::<Pong>::define(&espnow)?;
+    rquickjs::Class::<GetStats>::define(&espnow)?;
+    rquickjs::Class::<HubStats>::define(&espnow)?;
+    rquickjs::Class::<ListPeers>::define(&espnow)?;
+    rquickjs::Class::<PeerList>::define(&espnow)?;
+    rquickjs::Class::<TxStatus>::define(&espnow)?;
+    rquickjs::Class::<StopBroadcast>::define(&espnow)?;
+    rquickjs::Class::<ListBroadcasts>::define(&espnow)?;
+    rquickjs::Class::<BroadcastInfo>::define(&espnow)?;
+    rquickjs::Class::<BroadcastList>::define(&espnow)?;
+    rquickjs::Class::<FlushDeferred>::define(&espnow)?;
+    rquickjs::Class::<DiscardDeferred>::define(&espnow)?;
+    rquickjs::Class::<GetDeferred>::define(&espnow)?;
+    rquickjs::Class::<DeferredDepth>::define(&espnow)?;
+    rquickjs::Class::<DeferredStatus>::define(&espnow)?;
+    rquickjs::Class::<Schedule>::define(&espnow)?;
+    rquickjs::Class::<CancelSend>::define(&espnow)?;
+    rquickjs::Class::<GroupMember>::define(&espnow)?;
+    rquickjs::Class::<SendGroup>::define(&espnow)?;
+    rquickjs::Class::<MemberResult>::define(&espnow)?;
+    rquickjs::Class::<GroupStatus>::define(&espnow)?;
+    rquickjs::Class::<RxFilterRule>::define(&espnow)?;
+    rquickjs::Class::<RxFilter>::define(&espnow)?;
     rquickjs::Class::<Msg>::define(&espnow)?;
     espnow.set("parse_mac", crate::util::js_parse_mac)?;
     espnow.set("format_mac", crate::util::js_format_mac)?;
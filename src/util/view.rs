@@ -3,6 +3,10 @@ use core::fmt::Write;
 pub fn display_vec<const N: usize, const M: usize>(
     data: &heapless::Vec<u8, M>,
 ) -> heapless::String<N> {
+    display_slice(data)
+}
+
+pub fn display_slice<const N: usize>(data: &[u8]) -> heapless::String<N> {
     let mut output = heapless::String::<N>::new();
 
     // First, try full UTF-8 decode